@@ -1,6 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
-    io::Cursor,
+    io::{Cursor, Write as _},
     sync::{Arc, Mutex},
 };
 
@@ -8,64 +8,376 @@ use codee::{Decoder, Encoder};
 use futures::{StreamExt, stream::FuturesUnordered};
 use image::{DynamicImage, ImageReader, imageops::overlay};
 use leptos::{
-    html::{Button, Dialog},
+    ev,
+    html::{Button, Dialog, Input},
     leptos_dom::logging::{console_error, console_log, console_warn},
     prelude::*,
     task::spawn_local,
 };
-use leptos_use::on_click_outside;
-use leptos_use::storage::use_session_storage;
+use leptos_use::storage::{use_local_storage, use_session_storage};
+use leptos_use::{UseClipboardReturn, on_click_outside, use_clipboard};
 use nucleo_matcher::{
     Matcher,
     pattern::{CaseMatching, Normalization, Pattern},
 };
 use printpdf::{
-    ImageCompression, ImageOptimizationOptions, LinePoint, Mm, Op, PaintMode, PdfDocument, PdfPage,
-    PdfSaveOptions, Point, Polygon, PolygonRing, RawImage, WindingOrder, XObjectTransform,
+    BuiltinFont, ImageCompression, ImageOptimizationOptions, LinePoint, Mm, Op, PaintMode,
+    PdfDocument, PdfPage, PdfSaveOptions, Point, Polygon, PolygonRing, Pt, RawImage, TextItem,
+    WindingOrder, XObjectTransform,
 };
 use proxy_elev::{
     ACTIVE_LIBRARY, AlternateFaceMetadata, BleedMode, CardFacePrintingId, CardId, CutIndicator,
-    FilledCardSlot, InsertId, Library, MultiLibrary, PrintConfig, PrintFile, PrintSize,
+    FilledCardSlot, InsertId, Library, ManifestIndex, MultiLibrary, PrintConfig, PrintFile,
+    PrintHistory, PrintSize, PrintingPreference, ProjectFile, ProjectList, SLOTS_PER_PAGE,
+    SearchQuery, SharedProject, SlotPosition, bundled_library, decode_manifest, manifest_index,
+    preferred_image_root, set_preferred_image_root,
 };
 use reactive_stores::{Store, Subfield};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
 use web_sys::{Blob, Url, js_sys::Uint8Array};
 
 fn normalize_request_url(url: &str) -> String {
-    if url.starts_with('/') {
-        if let Some(window) = web_sys::window() {
-            if let Ok(origin) = window.location().origin() {
-                return format!("{origin}{url}");
-            }
-        }
+    if url.starts_with('/')
+        && let Some(window) = web_sys::window()
+        && let Ok(origin) = window.location().origin()
+    {
+        return format!("{origin}{url}");
     }
     url.to_string()
 }
 
+/// An image downloaded from the first of its candidate URLs to respond.
+struct DownloadedImage {
+    /// The URL the image was downloaded from.
+    url: String,
+    bytes: Vec<u8>,
+}
+
+impl DownloadedImage {
+    /// The file extension of the URL the image was downloaded from.
+    fn extension(&self) -> Option<&str> {
+        self.url
+            .rsplit_once('.')
+            .map(|(_, extension)| extension)
+            .filter(|extension| !extension.contains('/'))
+    }
+}
+
+/// Downloads the first of `urls` that is available, such as a localized card
+/// image followed by the English one it falls back to.
+async fn download_first(urls: &[String]) -> Result<DownloadedImage, String> {
+    for url in urls {
+        let Ok(resp) = reqwest::get(normalize_request_url(url)).await else {
+            continue;
+        };
+        if !resp.status().is_success() {
+            continue;
+        }
+        if let Ok(bytes) = resp.bytes().await {
+            remember_image_mirror(url);
+            return Ok(DownloadedImage {
+                url: url.clone(),
+                bytes: bytes.to_vec(),
+            });
+        }
+    }
+    Err(format!(
+        "Could not download {}",
+        urls.first().map_or("an image", String::as_str)
+    ))
+}
+
+/// Downloads the image of each slot. The first is downloaded on its own, so
+/// that the rest try the mirror it was found on first.
+async fn download_slot_images(
+    slots: &[FilledCardSlot],
+) -> Result<HashMap<FilledCardSlot, DownloadedImage>, String> {
+    let mut slots = slots.iter().cloned().collect::<HashSet<_>>().into_iter();
+    let mut downloaded = HashMap::new();
+    let Some(first) = slots.next() else {
+        return Ok(downloaded);
+    };
+    let image = download_first(&first.image_urls()).await?;
+    downloaded.insert(first, image);
+    let mut rest = slots
+        .map(|slot| async move {
+            let image = download_first(&slot.image_urls()).await?;
+            Ok::<_, String>((slot, image))
+        })
+        .collect::<FuturesUnordered<_>>();
+    while let Some(result) = rest.next().await {
+        let (slot, image) = result?;
+        downloaded.insert(slot, image);
+    }
+    Ok(downloaded)
+}
+
+fn decode_image(bytes: &[u8]) -> Result<DynamicImage, String> {
+    ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|err| err.to_string())?
+        .decode()
+        .map_err(|err| err.to_string())
+}
+
+/// Runs an export in the background, telling the user if it fails, and
+/// clears `printing` once it is done either way.
+fn spawn_export(
+    printing: Subfield<Store<AppState>, AppState, bool>,
+    export: impl Future<Output = Result<(), String>> + 'static,
+) {
+    printing.set(true);
+    spawn_local(async move {
+        if let Err(err) = export.await {
+            console_error(&format!("Export failed: {err}"));
+            let _ = window().alert_with_message(&format!("Export failed: {err}"));
+        }
+        printing.set(false);
+    });
+}
+
+const IMAGE_MIRROR_KEY: &str = "image-mirror-v0";
+
+fn session_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.session_storage().ok()?
+}
+
+/// Tries the image root that `url` was loaded from first for the rest of the
+/// session, so that an unreachable mirror is only waited on once.
+fn remember_image_mirror(url: &str) {
+    let Some(root) = with_library(|library| library.image_root_of(url)) else {
+        return;
+    };
+    if preferred_image_root().as_ref() == Some(&root) {
+        return;
+    }
+    if let Some(storage) = session_storage() {
+        let _ = storage.set_item(IMAGE_MIRROR_KEY, &root);
+    }
+    set_preferred_image_root(Some(root));
+}
+
+fn restore_image_mirror() {
+    let root = session_storage().and_then(|storage| storage.get_item(IMAGE_MIRROR_KEY).ok()?);
+    set_preferred_image_root(root);
+}
+
+/// Moves an image on to the next of its fallback URLs after it fails to load.
+fn next_fallback_image(image: &web_sys::HtmlImageElement, urls: &[String]) {
+    let current = image.get_attribute("src").unwrap_or_default();
+    if let Some(next) = urls.iter().skip_while(|url| **url != current).nth(1) {
+        image.set_src(next);
+    }
+}
+
 fn with_library<R>(f: impl FnOnce(&MultiLibrary) -> R) -> R {
     let lib = ACTIVE_LIBRARY.read().expect("library lock");
     f(&lib)
 }
 
+/// The signals backed by storage that the project helpers write to. Each call
+/// to a storage hook makes a separate signal that only sees the others' writes
+/// once they have been flushed to storage, so they are made once and shared.
+#[derive(Clone, Copy)]
+struct StoredSignals {
+    print_file: (Signal<PrintFile>, WriteSignal<PrintFile>),
+    print_config: (Signal<PrintConfig>, WriteSignal<PrintConfig>),
+    project_list: (Signal<ProjectList>, WriteSignal<ProjectList>),
+    active_project: (Signal<Option<u64>>, WriteSignal<Option<u64>>),
+}
+
+impl StoredSignals {
+    fn new() -> Self {
+        let (print_file, set_print_file, _delete) =
+            use_session_storage::<PrintFile, RonSerdeCodec>("print-set-v0");
+        let (print_config, set_print_config, _delete) =
+            use_session_storage::<PrintConfig, RonSerdeCodec>("print-config-v0");
+        let (project_list, set_project_list, _delete) =
+            use_local_storage::<ProjectList, RonSerdeCodec>("projects-v0");
+        // Kept per tab so that two tabs can work on different projects without
+        // overwriting each other.
+        let (active_project, set_active_project, _delete) =
+            use_session_storage::<Option<u64>, RonSerdeCodec>("active-project-v0");
+        Self {
+            print_file: (print_file, set_print_file),
+            print_config: (print_config, set_print_config),
+            project_list: (project_list, set_project_list),
+            active_project: (active_project, set_active_project),
+        }
+    }
+}
+
 fn use_print_file() -> (Signal<PrintFile>, WriteSignal<PrintFile>) {
-    let (get, set, _delete) = use_session_storage::<PrintFile, RonSerdeCodec>("print-set-v0");
-    (get, set)
+    expect_context::<StoredSignals>().print_file
 }
 
 fn use_print_config() -> (Signal<PrintConfig>, WriteSignal<PrintConfig>) {
-    let (get, set, _delete) = use_session_storage::<PrintConfig, RonSerdeCodec>("print-config-v0");
+    expect_context::<StoredSignals>().print_config
+}
+
+fn use_project_list() -> (Signal<ProjectList>, WriteSignal<ProjectList>) {
+    expect_context::<StoredSignals>().project_list
+}
+
+/// The project this tab is editing.
+fn use_active_project() -> (Signal<Option<u64>>, WriteSignal<Option<u64>>) {
+    expect_context::<StoredSignals>().active_project
+}
+
+fn use_printing_preference() -> (Signal<PrintingPreference>, WriteSignal<PrintingPreference>) {
+    let (get, set, _delete) =
+        use_local_storage::<PrintingPreference, RonSerdeCodec>("printing-preference-v0");
+    (get, set)
+}
+
+fn use_decklist_layout() -> (Signal<DecklistLayout>, WriteSignal<DecklistLayout>) {
+    let (get, set, _delete) =
+        use_local_storage::<DecklistLayout, RonSerdeCodec>("decklist-layout-v0");
     (get, set)
 }
 
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn now() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+const SHARE_LINK_PREFIX: &str = "#share=";
+
+fn share_link(print_file: &PrintFile, print_config: PrintConfig) -> String {
+    let location = window().location();
+    let origin = location.origin().unwrap_or_default();
+    let pathname = location.pathname().unwrap_or_default();
+    let encoded = SharedProject::new(print_file, print_config).encode();
+    format!("{origin}{pathname}{SHARE_LINK_PREFIX}{encoded}")
+}
+
+/// Switches between saved projects. Made in a component's body, since its
+/// methods are called from event handlers where there is no context.
+#[derive(Clone, Copy)]
+struct ProjectSwitcher {
+    set_print_file: WriteSignal<PrintFile>,
+    set_print_config: WriteSignal<PrintConfig>,
+    project_list: Signal<ProjectList>,
+    set_project_list: WriteSignal<ProjectList>,
+    active_project: Signal<Option<u64>>,
+    set_active_project: WriteSignal<Option<u64>>,
+    history: Subfield<Store<AppState>, AppState, PrintHistory>,
+}
+fn use_project_switcher() -> ProjectSwitcher {
+    let (_, set_print_file) = use_print_file();
+    let (_, set_print_config) = use_print_config();
+    let (project_list, set_project_list) = use_project_list();
+    let (active_project, set_active_project) = use_active_project();
+    ProjectSwitcher {
+        set_print_file,
+        set_print_config,
+        project_list,
+        set_project_list,
+        active_project,
+        set_active_project,
+        history: use_history(),
+    }
+}
+impl ProjectSwitcher {
+    /// Loads a saved project into the working copy and makes it the active one.
+    fn switch(self, id: u64) {
+        let Some(project) = self
+            .project_list
+            .with_untracked(|list| list.get(id).cloned())
+        else {
+            return;
+        };
+        self.set_active_project.set(Some(id));
+        self.set_print_file.set(project.print_file);
+        self.set_print_config.set(project.print_config);
+        self.history.write().clear();
+    }
+
+    /// Saves a new project and switches to it.
+    fn create(self, name: String, print_file: PrintFile, print_config: PrintConfig) {
+        let mut id = 0;
+        self.set_project_list.update(|list| {
+            id = list.create(name, print_file, print_config, now());
+        });
+        self.switch(id);
+    }
+
+    /// Deletes a saved project, moving this tab to another one if it was active.
+    fn delete(self, id: u64) {
+        self.set_project_list.update(|list| list.delete(id));
+        if self.active_project.get_untracked() == Some(id) {
+            self.open_most_recent();
+        }
+    }
+
+    /// Opens the most recently changed project, creating an empty one if none exist.
+    fn open_most_recent(self) {
+        match self
+            .project_list
+            .with_untracked(|list| list.most_recent().map(|p| p.id))
+        {
+            Some(id) => self.switch(id),
+            None => self.create(
+                "Untitled project".to_string(),
+                PrintFile::default(),
+                PrintConfig::default(),
+            ),
+        }
+    }
+
+    /// Restores a project shared through the URL fragment as a new project, then
+    /// drops the fragment so that reloading does not import it again.
+    fn open_share_link(self) {
+        let location = window().location();
+        let Ok(hash) = location.hash() else {
+            return;
+        };
+        let Some(encoded) = hash.strip_prefix(SHARE_LINK_PREFIX) else {
+            return;
+        };
+        match SharedProject::decode(encoded).and_then(SharedProject::into_project) {
+            Ok((print_file, print_config)) => {
+                self.create("Shared project".to_string(), print_file, print_config);
+            }
+            Err(err) => console_warn(&format!("Failed to open share link: {err}")),
+        }
+        let url = format!(
+            "{}{}",
+            location.pathname().unwrap_or_default(),
+            location.search().unwrap_or_default()
+        );
+        if let Ok(history) = window().history() {
+            let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&url));
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum OpenDialog {
     Edit(usize),
     Print,
     TtsExport,
+    ImageExport,
     JnetImport,
     NrdbImport,
+    DecklistExport,
+    Project,
+    PrintingPreference,
+    AddSet,
+}
+
+/// How the cards in the print file are shown on the main page.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DecklistLayout {
+    /// One thumbnail per slot, in print order.
+    #[default]
+    Grid,
+    /// One row per printing, with its count.
+    List,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -83,6 +395,7 @@ pub struct AppState {
     import_status: Option<ImportStatus>,
     selected_library: String,
     library_version: u32,
+    history: PrintHistory,
 }
 fn use_open_dialog() -> Subfield<Store<AppState>, AppState, Option<OpenDialog>> {
     expect_context::<Store<AppState>>().dialog()
@@ -99,6 +412,51 @@ fn use_import_status() -> Subfield<Store<AppState>, AppState, Option<ImportStatu
 fn use_library_version() -> Subfield<Store<AppState>, AppState, u32> {
     expect_context::<Store<AppState>>().library_version()
 }
+fn use_history() -> Subfield<Store<AppState>, AppState, PrintHistory> {
+    expect_context::<Store<AppState>>().history()
+}
+
+/// Changes the print file while keeping track of undo history. Everything
+/// that edits the cards in the print file should go through this.
+#[derive(Clone, Copy)]
+struct PrintFileEditor {
+    print_file: Signal<PrintFile>,
+    set_print_file: WriteSignal<PrintFile>,
+    history: Subfield<Store<AppState>, AppState, PrintHistory>,
+}
+fn use_print_file_editor() -> PrintFileEditor {
+    let (print_file, set_print_file) = use_print_file();
+    PrintFileEditor {
+        print_file,
+        set_print_file,
+        history: use_history(),
+    }
+}
+impl PrintFileEditor {
+    /// Applies `edit` as a single undo step. Edits that leave the print file
+    /// unchanged are not recorded.
+    fn edit(self, edit: impl FnOnce(&mut PrintFile)) {
+        let before = self.print_file.get_untracked();
+        let mut after = before.clone();
+        edit(&mut after);
+        if after != before {
+            self.history.write().record(before);
+            self.set_print_file.set(after);
+        }
+    }
+    fn undo(self) {
+        let current = self.print_file.get_untracked();
+        if let Some(previous) = self.history.write().undo(current) {
+            self.set_print_file.set(previous);
+        }
+    }
+    fn redo(self) {
+        let current = self.print_file.get_untracked();
+        if let Some(next) = self.history.write().redo(current) {
+            self.set_print_file.set(next);
+        }
+    }
+}
 
 pub struct RonSerdeCodec;
 
@@ -125,29 +483,119 @@ where
 
 fn main() {
     console_error_panic_hook::set_once();
+    restore_image_mirror();
     leptos::mount::mount_to_body(Root);
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+/// The print groups whose manifests have been fetched, or are being fetched,
+/// so that each group is only requested once.
+#[derive(Clone, Debug, Default, PartialEq)]
 struct Libraries {
     loaded_libraries: HashSet<String>,
-    library: Library,
-}
-impl Default for Libraries {
-    fn default() -> Libraries {
-        let mut base_state = Libraries {
-            loaded_libraries: HashSet::new(),
-            library: Library {
-                cards: HashMap::new(),
-                faces: HashMap::new(),
-                inserts: HashMap::new(),
-            },
+}
+impl Libraries {
+    /// Marks `group` as loading, returning whether it still needed loading.
+    fn start_loading(&mut self, group: &str) -> bool {
+        self.loaded_libraries.insert(group.to_string())
+    }
+}
+
+/// Where the manifests deployed alongside this build are served from.
+const BUNDLED_MANIFEST_URL: &str = "/manifest";
+/// Where to look for a newer manifest at startup, which can be set with the
+/// `MANIFEST_URL` environment variable when building. Uploading a manifest
+/// there makes new cards available without rebuilding the site.
+const MANIFEST_URL: &str = match option_env!("MANIFEST_URL") {
+    Some(url) => url,
+    None => BUNDLED_MANIFEST_URL,
+};
+
+async fn fetch_manifest<T: serde::de::DeserializeOwned>(url: &str) -> Option<T> {
+    let url = normalize_request_url(url);
+    let resp = reqwest::get(&url).await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let bytes = resp.bytes().await.ok()?;
+    match decode_manifest(&bytes) {
+        Ok(manifest) => Some(manifest),
+        Err(err) => {
+            console_warn(&format!("Failed to load {url}: {err}"));
+            None
+        }
+    }
+}
+
+/// Switches to the manifest at [`MANIFEST_URL`] if it is newer than the
+/// compiled-in one, returning where the chosen manifest's groups are served
+/// from. The compiled-in manifest is kept when the newer one is unreachable.
+async fn choose_manifest() -> &'static str {
+    let Some(index) = fetch_manifest::<ManifestIndex>(&format!("{MANIFEST_URL}/index.bin")).await
+    else {
+        return BUNDLED_MANIFEST_URL;
+    };
+    if index.revision <= manifest_index().revision {
+        return BUNDLED_MANIFEST_URL;
+    }
+    console_log(&format!("Using manifest revision {}", index.revision));
+    *ACTIVE_LIBRARY.write().expect("library lock") = MultiLibrary::from_index(index);
+    MANIFEST_URL
+}
+
+/// Fetches a print group's manifest and adds it to the active library. Groups
+/// compiled into the client are used as they are when the compiled-in manifest
+/// was chosen, and otherwise only when fetching them fails.
+async fn load_library(manifest_url: &str, group: String) -> bool {
+    let library = if manifest_url == BUNDLED_MANIFEST_URL
+        && let Some(library) = bundled_library(&group)
+    {
+        library
+    } else if let Some(library) =
+        fetch_manifest::<Library>(&format!("{manifest_url}/{group}.bin")).await
+    {
+        library
+    } else if let Some(library) = bundled_library(&group) {
+        console_warn(&format!(
+            "Failed to load the {group} manifest, using the compiled-in one"
+        ));
+        library
+    } else {
+        console_warn(&format!("Failed to load the {group} manifest"));
+        return false;
+    };
+    ACTIVE_LIBRARY
+        .write()
+        .expect("library lock")
+        .insert_library(group, library);
+    true
+}
+
+async fn load_local_overlay() -> Option<MultiLibrary> {
+    let urls = [
+        "/local-assets/manifest.local.ron",
+        "/manifest.local.ron",
+    ];
+    let mut overlay_text = None;
+    for url in urls {
+        let url = normalize_request_url(url);
+        let Ok(resp) = reqwest::get(&url).await else {
+            continue;
+        };
+        if !resp.status().is_success() {
+            continue;
+        }
+        let Ok(text) = resp.text().await else {
+            console_warn("Failed to read local overlay");
+            continue;
         };
-        let lib = with_library(|library| library.libraries["english"].clone());
-        base_state.library.merge(&lib);
-        base_state.loaded_libraries.insert("english".to_string());
-        base_state
+        overlay_text = Some(text);
+        break;
     }
+    let Ok(overlay) = ron::de::from_str::<MultiLibrary>(&overlay_text?) else {
+        console_warn("Failed to parse local overlay");
+        return None;
+    };
+    Some(overlay)
 }
 
 #[component]
@@ -158,47 +606,144 @@ fn Root() -> impl IntoView {
         import_status: None,
         selected_library: "english".to_string(),
         library_version: 0,
+        history: PrintHistory::default(),
     }));
+    provide_context(StoredSignals::new());
     let library_version = use_library_version();
-    spawn_local(async move {
-        let urls = [
-            "/local-assets/manifest.local.ron",
-            "/manifest.local.ron",
-        ];
-        let mut overlay_text = None;
-        for url in urls {
-            let url = normalize_request_url(url);
-            let Ok(resp) = reqwest::get(&url).await else {
-                continue;
-            };
-            if !resp.status().is_success() {
-                continue;
-            }
-            let Ok(text) = resp.text().await else {
-                console_warn("Failed to read local overlay");
-                continue;
-            };
-            overlay_text = Some(text);
-            break;
+
+    let (print_file, set_print_file) = use_print_file();
+    let (print_config, _) = use_print_config();
+    let (project_list, set_project_list) = use_project_list();
+    let (active_project, _) = use_active_project();
+    let switcher = use_project_switcher();
+    let has_active_project = active_project
+        .get_untracked()
+        .is_some_and(|id| project_list.with_untracked(|list| list.get(id).is_some()));
+    if !has_active_project {
+        if project_list.with_untracked(|list| list.all().is_empty()) {
+            // Keep whatever this tab was already working on as the first project.
+            switcher.create(
+                "Untitled project".to_string(),
+                print_file.get_untracked(),
+                print_config.get_untracked(),
+            );
+        } else {
+            switcher.open_most_recent();
         }
-        let Some(overlay_text) = overlay_text else {
+    }
+    switcher.open_share_link();
+
+    let (decklist_layout, _) = use_decklist_layout();
+    let editor = use_print_file_editor();
+    let _ = window_event_listener(ev::keydown, move |ev| {
+        if !(ev.ctrl_key() || ev.meta_key()) {
             return;
-        };
-        let Ok(overlay) = ron::de::from_str::<MultiLibrary>(&overlay_text) else {
-            console_warn("Failed to parse local overlay");
+        }
+        // Leave text fields to the browser's own undo.
+        let in_text_field = ev
+            .target()
+            .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+            .is_some_and(|element| matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA"));
+        if in_text_field {
+            return;
+        }
+        match ev.key().to_lowercase().as_str() {
+            "z" if ev.shift_key() => editor.redo(),
+            "z" => editor.undo(),
+            "y" => editor.redo(),
+            _ => return,
+        }
+        ev.prevent_default();
+    });
+    Effect::new(move |_| {
+        let Some(id) = active_project.get() else {
             return;
         };
-        {
-            let mut lib = ACTIVE_LIBRARY.write().expect("library lock");
-            lib.merge_overlay(overlay);
+        print_file.with(|print_file| {
+            print_config.with(|print_config| {
+                set_project_list
+                    .maybe_update(|list| list.update(id, print_file, print_config, now()));
+            });
+        });
+    });
+
+    // Groups are loaded once it is known which manifest they come from.
+    let manifest_url = RwSignal::new(None::<&'static str>);
+    spawn_local(async move {
+        let url = choose_manifest().await;
+        if let Some(overlay) = load_local_overlay().await {
+            ACTIVE_LIBRARY
+                .write()
+                .expect("library lock")
+                .merge_overlay(overlay);
         }
+        manifest_url.set(Some(url));
         library_version.update(|version| *version += 1);
     });
+
+    // Only the groups that are selected or used by the print file are loaded,
+    // along with the groups they fall back to for art. The edit dialog offers
+    // every group that has the card, so all groups are loaded once it opens.
+    let libraries = StoredValue::new(Libraries::default());
+    let selected_library = use_selected_library();
+    let open_dialog = use_open_dialog();
+    Effect::new(move |_| {
+        let Some(manifest_url) = manifest_url.get() else {
+            return;
+        };
+        let mut groups = print_file.with(|print_file| {
+            print_file
+                .all()
+                .iter()
+                .map(|slot| slot.print_group().to_string())
+                .collect::<HashSet<_>>()
+        });
+        groups.insert(selected_library.get());
+        if matches!(open_dialog.get(), Some(OpenDialog::Edit(_))) {
+            groups.extend(with_library(|library| {
+                library.collection_names.keys().cloned().collect::<Vec<_>>()
+            }));
+        }
+        let groups = with_library(|library| {
+            groups
+                .iter()
+                .flat_map(|group| library.group_with_fallbacks(group))
+                .collect::<HashSet<_>>()
+        });
+        for group in groups {
+            let needs_loading = libraries
+                .try_update_value(|libraries| libraries.start_loading(&group))
+                .unwrap_or_default();
+            if !needs_loading {
+                continue;
+            }
+            spawn_local(async move {
+                if load_library(manifest_url, group.clone()).await {
+                    library_version.update(|version| *version += 1);
+                } else {
+                    // Let a later change try again.
+                    libraries.update_value(|libraries| {
+                        libraries.loaded_libraries.remove(&group);
+                    });
+                }
+            });
+        }
+    });
+    // Variants can only be counted once their group is loaded. Recounting them
+    // is not an edit, so it is not recorded in the undo history.
+    Effect::new(move |_| {
+        library_version.track();
+        set_print_file.maybe_update(PrintFile::recount_variants);
+    });
+
     view! {
         <div class="bg-zinc-900 grid auto-rows-[min-content_1fr_min-content] gap-2 h-screen">
             <InputLineNew />
             <div class="p-4 overflow-y-scroll">
-                <DecklistView />
+                {move || match decklist_layout.get() {
+                    DecklistLayout::Grid => view! { <DecklistView /> }.into_any(),
+                    DecklistLayout::List => view! { <QuantityListView /> }.into_any(),
+                }}
                 <OpenDialog />
             </div>
             <div>
@@ -208,9 +753,16 @@ fn Root() -> impl IntoView {
     }
 }
 
+/// How many search results to show for a plain title search.
+const SEARCH_RESULTS: usize = 5;
+/// How many search results to show once the search has filters, which tend to
+/// match many cards that are all equally relevant.
+const FILTERED_SEARCH_RESULTS: usize = 50;
+
 #[component]
 fn InputLineNew() -> impl IntoView {
-    let (_, set_print_file) = use_print_file();
+    let editor = use_print_file_editor();
+    let (printing_preference, _) = use_printing_preference();
     let selected_library = use_selected_library();
     let library_version = use_library_version();
     let mut matcher_config = nucleo_matcher::Config::DEFAULT;
@@ -227,8 +779,15 @@ fn InputLineNew() -> impl IntoView {
             haystack: vec![],
             mappings: HashMap::new(),
         };
-        let library =
-            with_library(|library| library.libraries[&selected_library.get()].clone());
+        // The group may still be loading, in which case there is nothing to
+        // search yet.
+        let library = with_library(|library| {
+            library
+                .libraries
+                .get(&selected_library.get())
+                .cloned()
+                .unwrap_or_default()
+        });
         for (card, meta) in &library.cards {
             haystack.haystack.push(meta.title.title.clone());
             haystack.haystack.push(meta.title.stripped_title.clone());
@@ -240,6 +799,35 @@ fn InputLineNew() -> impl IntoView {
                 HaystackEntry::Card(card.clone()),
             );
         }
+        // Localized groups can also be searched by the titles of the same cards
+        // in the group they fall back to.
+        let fallback_titles = with_library(|multi_library| {
+            let Some(fallback) = multi_library
+                .fallbacks
+                .get(&selected_library.get())
+                .and_then(|fallback| multi_library.libraries.get(fallback))
+            else {
+                return Vec::new();
+            };
+            library
+                .cards
+                .keys()
+                .filter_map(|card| {
+                    let meta = fallback.try_get_card(card)?;
+                    Some((card.clone(), meta.title.clone()))
+                })
+                .collect::<Vec<_>>()
+        });
+        for (card, title) in fallback_titles {
+            for title in [title.title, title.stripped_title] {
+                if !haystack.mappings.contains_key(&title) {
+                    haystack.haystack.push(title.clone());
+                    haystack
+                        .mappings
+                        .insert(title, HaystackEntry::Card(card.clone()));
+                }
+            }
+        }
         for (insert, meta) in &library.inserts {
             haystack.haystack.push(meta.title.title.clone());
             haystack.haystack.push(meta.title.stripped_title.clone());
@@ -264,21 +852,103 @@ fn InputLineNew() -> impl IntoView {
 
     let found = Memo::new(move |_| {
         let haystack = haystack.read();
-        let pattern = Pattern::parse(&input.get(), CaseMatching::Ignore, Normalization::Smart);
-        let out = pattern.match_list(&haystack.haystack, &mut matcher.lock().unwrap());
-        let mut found = HashSet::new();
-        let mut olist = Vec::with_capacity(5);
-        for (entry, _) in out {
-            let entry = &haystack.mappings[entry];
-            if found.insert(entry) {
-                olist.push(entry.clone());
+        let query = SearchQuery::parse(&input.get());
+        let limit = if query.is_filtered() {
+            FILTERED_SEARCH_RESULTS
+        } else {
+            SEARCH_RESULTS
+        };
+        with_library(|library| {
+            let Some(library) = library.libraries.get(&selected_library.get()) else {
+                return Vec::new();
+            };
+            if query.title.is_empty() {
+                // Filters alone list every matching card, alphabetically.
+                let mut cards = library
+                    .cards
+                    .values()
+                    .filter(|card| query.is_filtered() && query.matches(library, card))
+                    .collect::<Vec<_>>();
+                cards.sort_by(|a, b| a.title.title.cmp(&b.title.title));
+                return cards
+                    .into_iter()
+                    .take(limit)
+                    .map(|card| HaystackEntry::Card(card.id.clone()))
+                    .collect();
             }
-            if found.len() == 5 {
-                break;
+            let pattern = Pattern::parse(&query.title, CaseMatching::Ignore, Normalization::Smart);
+            let out = pattern.match_list(&haystack.haystack, &mut matcher.lock().unwrap());
+            let mut found = HashSet::new();
+            let mut olist = Vec::with_capacity(limit);
+            for (entry, _) in out {
+                let entry = &haystack.mappings[entry];
+                let passes = match entry {
+                    HaystackEntry::Card(card) => library
+                        .try_get_card(card)
+                        .is_some_and(|card| query.matches(library, card)),
+                    HaystackEntry::Insert(_) | HaystackEntry::InsertGroup(_) => {
+                        !query.is_filtered()
+                    }
+                };
+                if passes && found.insert(entry) {
+                    olist.push(entry.clone());
+                }
+                if found.len() == limit {
+                    break;
+                }
             }
-        }
-        olist
+            olist
+        })
+    });
+
+    let (selected, set_selected) = signal(0_usize);
+    // Go back to the first result whenever the results change, including when
+    // a group finishes loading, so the selection never points past the end.
+    Effect::new(move |_| {
+        found.track();
+        set_selected.set(0);
+    });
+    let collections = Memo::new(move |_| {
+        let _ = library_version.get();
+        with_library(MultiLibrary::collections)
     });
+    let copies = Memo::new(move |_| SearchQuery::parse(&input.get()).copies());
+
+    let add_entry = move |entry: &HaystackEntry| {
+        let copies = copies.get_untracked();
+        editor.edit(|print_file| match entry {
+            HaystackEntry::Card(card) => {
+                let card = with_library(|library| {
+                    library.libraries[&selected_library.get()]
+                        .get_card(card)
+                        .clone()
+                });
+                let printing_preference = printing_preference.get_untracked();
+                for _ in 0..copies {
+                    print_file.add_cards(&card, &printing_preference);
+                }
+            }
+            HaystackEntry::Insert(insert) => {
+                for _ in 0..copies {
+                    print_file.add_insert(insert.clone());
+                }
+            }
+            HaystackEntry::InsertGroup(group) => {
+                let inserts = with_library(|library| {
+                    library.libraries[&selected_library.get()]
+                        .inserts
+                        .values()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                });
+                for insert in inserts {
+                    if insert.insert_groups.contains(group) {
+                        print_file.add_insert(insert.id.clone());
+                    }
+                }
+            }
+        });
+    };
 
     let foundlist = move || {
         if input.read().is_empty() || found.get().is_empty() {
@@ -299,65 +969,67 @@ fn InputLineNew() -> impl IntoView {
                         each=move || found.get().into_iter().enumerate()
                         key=|(i, entry)| (*i, entry.clone())
                         children=move |(i, entry)| {
-                            let name = match entry {
-                                HaystackEntry::Card(card) => with_library(|library| {
-                                    library.libraries[&selected_library.get()]
-                                        .get_card(&card)
-                                        .title
-                                        .title
-                                        .clone()
-                                }),
-                                HaystackEntry::Insert(insert) => with_library(|library| {
-                                    library.libraries[&selected_library.get()]
-                                        .get_insert(&insert)
-                                        .title
-                                        .title
-                                        .clone()
-                                }),
-                                HaystackEntry::InsertGroup(group) => group.clone(),
-                            };
-                            let classes = if i == 0 {
-                                "bg-blue-800 hover:bg-blue-600 py-2 px-4 rounded-lg cursor-pointer text-nowrap"
-                            } else {
-                                "bg-slate-800 hover:bg-slate-600 py-2 px-4 rounded-lg cursor-pointer text-nowrap"
-                            };
+                            let (name, image_slot, printing_name) = with_library(|multi_library| {
+                                let library = &multi_library.libraries[&selected_library.get()];
+                                match &entry {
+                                    HaystackEntry::Card(card) => {
+                                        let card = library.get_card(card);
+                                        let printing = printing_preference.with_untracked(|preference| {
+                                            card.printing_face(preference.choose(multi_library, card), Some(1))
+                                                .cloned()
+                                        });
+                                        let printing_name = printing
+                                            .as_ref()
+                                            .and_then(|printing| library.faces.get(printing))
+                                            .map(|printing| printing.printing_name.clone());
+                                        (
+                                            card.title.title.clone(),
+                                            printing.map(|printing| FilledCardSlot::Card { printing }),
+                                            printing_name,
+                                        )
+                                    }
+                                    HaystackEntry::Insert(insert) => (
+                                        library.get_insert(insert).title.title.clone(),
+                                        Some(FilledCardSlot::Insert { insert: insert.clone() }),
+                                        Some("Insert".to_string()),
+                                    ),
+                                    HaystackEntry::InsertGroup(group) => {
+                                        (group.clone(), None, Some("Insert group".to_string()))
+                                    }
+                                }
+                            });
+                            let image_urls = image_slot.as_ref().map(FilledCardSlot::thumbnail_urls);
+                            let is_selected = Memo::new(move |_| selected.get() == i);
+                            let not_selected = Memo::new(move |_| !is_selected.get());
+                            let entry = StoredValue::new(entry);
                             view! {
                                 <button
                                     type="button"
-                                    on:click=move |_| {
-                                        match &found.read()[i] {
-                                            HaystackEntry::Card(card) => {
-                                                let card = with_library(|library| {
-                                                    library.libraries[&selected_library.get()]
-                                                        .get_card(card)
-                                                        .clone()
-                                                });
-                                                set_print_file.write().add_cards(&card);
-                                            }
-                                            HaystackEntry::Insert(insert) => {
-                                                set_print_file.write().add_insert(insert.clone());
-                                            }
-                                            HaystackEntry::InsertGroup(group) => {
-                                                let mut set_print_file = set_print_file.write();
-                                                let inserts = with_library(|library| {
-                                                    library.libraries[&selected_library.get()]
-                                                        .inserts
-                                                        .values()
-                                                        .cloned()
-                                                        .collect::<Vec<_>>()
-                                                });
-                                                for insert in inserts {
-                                                    if insert.insert_groups.contains(group) {
-                                                        set_print_file.add_insert(insert.id.clone());
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
+                                    on:click=move |_| entry.with_value(add_entry)
+                                    on:mouseenter=move |_| set_selected.set(i)
                                     value={i}
-                                    class={classes}
+                                    class="hover:bg-blue-600 py-1 px-2 rounded-lg cursor-pointer text-nowrap flex gap-2 items-center text-left"
+                                    class:bg-blue-800=is_selected
+                                    class:bg-slate-800=not_selected
                                 >
-                                    {name}
+                                    {image_urls.map(|image_urls| view! {
+                                        <img
+                                            class="w-10 rounded-sm"
+                                            src=image_urls[0].clone()
+                                            alt=""
+                                            on:load:target=move |ev| remember_image_mirror(&ev.target().src())
+                                            on:error:target=move |ev| {
+                                                next_fallback_image(&ev.target(), &image_urls);
+                                            }
+                                        />
+                                    })}
+                                    <span class="flex flex-col">
+                                        <span>
+                                            {move || (copies.get() > 1).then(|| format!("{}x ", copies.get()))}
+                                            {name}
+                                        </span>
+                                        <span class="text-xs text-zinc-400">{printing_name}</span>
+                                    </span>
                                 </button>
                             }
                         }
@@ -373,51 +1045,55 @@ fn InputLineNew() -> impl IntoView {
                 class="grid grid-cols-[1fr_min-content] md:grid-cols-[min-content_1fr_min-content] gap-2"
                 on:submit=move |v| {
                     v.prevent_default();
-                    let found = found.read();
-                    if found.is_empty() {
-                        return;
+                    let entry = found.read().get(selected.get_untracked()).cloned();
+                    if let Some(entry) = entry {
+                        add_entry(&entry);
                     }
-                    match &found[0] {
-                        HaystackEntry::Card(card) => {
-                            let card = with_library(|library| {
-                                library.libraries[&selected_library.get()]
-                                    .get_card(card)
-                                    .clone()
-                            });
-                            set_print_file.write().add_cards(&card);
-                        }
-                        HaystackEntry::Insert(insert) => {
-                            set_print_file.write().add_insert(insert.clone());
-                        }
-                        HaystackEntry::InsertGroup(group) => {
-                            let mut set_print_file = set_print_file.write();
-                            let inserts = with_library(|library| {
-                                library.libraries[&selected_library.get()]
-                                    .inserts
-                                    .values()
-                                    .cloned()
-                                    .collect::<Vec<_>>()
+                }
+            >
+                <select
+                    class="col-span-full md:col-[unset] px-2"
+                    on:change:target=move |ev| {
+                        selected_library.set(ev.target().value());
+                    }
+                >
+                    <For
+                        each=move || collections.get()
+                        key=|collection| collection.clone()
+                        children=move |(group, name)| {
+                            let is_selected = Memo::new({
+                                let group = group.clone();
+                                move |_| selected_library.get() == group
                             });
-                            for insert in inserts {
-                                if insert.insert_groups.contains(group) {
-                                    set_print_file.add_insert(insert.id.clone());
-                                }
+                            view! {
+                                <option value=group prop:selected=is_selected>{name}</option>
                             }
                         }
-                    }
-
-                }
-            >
-                <select class="col-span-full md:col-[unset] px-2">
-                    <option value="english">{"NSG English"}</option>
-                    <option value="nsg" disabled>{"WIP"}</option>
+                    />
                 </select>
                 <input
                     type="text"
                     on:input:target=move |ev| {
                         set_input.set(ev.target().value());
                     }
+                    on:keydown=move |ev| {
+                        let results = found.read().len();
+                        match ev.key().as_str() {
+                            "ArrowDown" if results > 0 => {
+                                ev.prevent_default();
+                                set_selected.update(|selected| *selected = (*selected + 1) % results);
+                            }
+                            "ArrowUp" if results > 0 => {
+                                ev.prevent_default();
+                                set_selected.update(|selected| {
+                                    *selected = (*selected + results - 1) % results;
+                                });
+                            }
+                            _ => {}
+                        }
+                    }
                     name="data-search"
+                    placeholder="Search titles, or filter with f: d: t: s: x:"
                     class="bg-zinc-900 border-1 border-white py-2 px-4 rounded-md"
                     prop:value=input
                 />
@@ -442,6 +1118,9 @@ fn DecklistView() -> impl IntoView {
     let open_dialog = use_open_dialog();
     let library_version = use_library_version();
     let num_items = Memo::new(move |_| print_file.with(PrintFile::len));
+    let editor = use_print_file_editor();
+    let (dragged, set_dragged) = signal(None::<usize>);
+    let (drop_target, set_drop_target) = signal(None::<usize>);
     view! {
         <div class="flex flex-wrap gap-2 justify-center">
             <For
@@ -460,10 +1139,13 @@ fn DecklistView() -> impl IntoView {
                         let _ = library_version.get();
                         card.as_ref().map(FilledCardSlot::name).unwrap_or_default()
                     }));
-                    let image_url = Memo::new(move |_| card.with(|card| {
+                    let image_urls = Memo::new(move |_| card.with(|card| {
                         let _ = library_version.get();
-                        card.as_ref().map(FilledCardSlot::image_url).unwrap_or_default()
+                        card.as_ref().map(FilledCardSlot::thumbnail_urls).unwrap_or_default()
                     }));
+                    let image_url = Memo::new(move |_| {
+                        image_urls.with(|urls| urls.first().cloned().unwrap_or_default())
+                    });
                     let is_local_override = Memo::new(move |_| {
                         let _ = library_version.get();
                         card.with(|card| {
@@ -473,10 +1155,50 @@ fn DecklistView() -> impl IntoView {
                     });
                     view! {
                         <button
-                            class="relative"
+                            class="relative outline-zinc-300 outline-offset-2"
+                            class:opacity-50=move || dragged.get() == Some(i)
+                            class:outline-2=move || drop_target.get() == Some(i)
+                            class:outline-dashed=move || drop_target.get() == Some(i)
+                            title="Drag to move, hold Shift while dropping to swap"
+                            draggable="true"
                             on:click:target=move |_| {
                                 open_dialog.set(Some(OpenDialog::Edit(i)));
                             }
+                            on:dragstart=move |ev| {
+                                // Firefox only starts a drag when some data is set.
+                                if let Some(data) = ev.data_transfer() {
+                                    let _ = data.set_data("text/plain", &i.to_string());
+                                    data.set_effect_allowed("move");
+                                }
+                                set_dragged.set(Some(i));
+                            }
+                            on:dragover=move |ev| {
+                                if dragged.get_untracked().is_some() {
+                                    ev.prevent_default();
+                                    set_drop_target.set(Some(i));
+                                }
+                            }
+                            on:dragleave=move |_| {
+                                if drop_target.get_untracked() == Some(i) {
+                                    set_drop_target.set(None);
+                                }
+                            }
+                            on:drop=move |ev| {
+                                ev.prevent_default();
+                                if let Some(from) = dragged.get_untracked() {
+                                    if ev.shift_key() {
+                                        editor.edit(|print_file| print_file.swap_slots(from, i));
+                                    } else {
+                                        editor.edit(|print_file| print_file.move_slot(from, i));
+                                    }
+                                }
+                                set_dragged.set(None);
+                                set_drop_target.set(None);
+                            }
+                            on:dragend=move |_| {
+                                set_dragged.set(None);
+                                set_drop_target.set(None);
+                            }
                         >
                             <Show when=move || is_local_override.get()>
                                 <span class="absolute top-1 right-1 z-10 text-[10px] font-bold bg-amber-500 text-black px-1 rounded">
@@ -488,6 +1210,10 @@ fn DecklistView() -> impl IntoView {
                                 class="ring-blue-800 w-24 cursor-pointer"
                                 src=image_url
                                 alt=name
+                                on:load:target=move |ev| remember_image_mirror(&ev.target().src())
+                                on:error:target=move |ev| {
+                                    next_fallback_image(&ev.target(), &image_urls.get_untracked());
+                                }
                             />
                         </button>
                     }
@@ -498,11 +1224,118 @@ fn DecklistView() -> impl IntoView {
 }
 
 #[component]
-fn OpenDialog() -> impl IntoView {
-    let open_dialog = use_open_dialog();
-
-    let modal_ref = NodeRef::<Dialog>::new();
-    let _ = on_click_outside(modal_ref, move |_| open_dialog.set(None));
+fn QuantityListView() -> impl IntoView {
+    let (print_file, _) = use_print_file();
+    let editor = use_print_file_editor();
+    let library_version = use_library_version();
+    let cards = Memo::new(move |_| {
+        let _ = library_version.get();
+        print_file.with(PrintFile::decklist)
+    });
+    let inserts = Memo::new(move |_| {
+        let _ = library_version.get();
+        print_file.with(|print_file| {
+            let mut inserts: Vec<(InsertId, String, usize)> = Vec::new();
+            for slot in print_file.all() {
+                if let FilledCardSlot::Insert { insert } = slot {
+                    match inserts.iter_mut().find(|(id, _, _)| id == insert) {
+                        Some((_, _, count)) => *count += 1,
+                        None => inserts.push((insert.clone(), slot.name(), 1)),
+                    }
+                }
+            }
+            inserts
+        })
+    });
+    view! {
+        <div class="grid grid-cols-[min-content_min-content_1fr_min-content] gap-2 items-center max-w-2xl mx-auto">
+            <For
+                each=move || cards.get()
+                key=|entry| entry.clone()
+                children=move |entry| {
+                    let remove = entry.clone();
+                    view! {
+                        <button
+                            class="bg-zinc-800 hover:bg-zinc-600 px-3 py-1 rounded-lg cursor-pointer"
+                            on:click:target=move |_| {
+                                editor.edit(|print_file| {
+                                    print_file.remove_copy(remove.printing_id, &remove.print_group);
+                                });
+                            }
+                        >
+                            {"-"}
+                        </button>
+                        <p class="text-right font-bold">{entry.count}</p>
+                        <p>
+                            {entry.title.title.clone()}
+                            <span class="text-zinc-400">{" ("}{entry.printing_name.clone()}{")"}</span>
+                        </p>
+                        <button
+                            class="bg-zinc-800 hover:bg-zinc-600 px-3 py-1 rounded-lg cursor-pointer"
+                            on:click:target=move |_| {
+                                let card = with_library(|library| {
+                                    library
+                                        .libraries
+                                        .get(&entry.print_group)
+                                        .and_then(|group| group.try_get_card(&entry.card_id))
+                                        .cloned()
+                                });
+                                if let Some(card) = card {
+                                    editor.edit(|print_file| {
+                                        print_file.add_card_printing(&card, entry.printing_id);
+                                    });
+                                }
+                            }
+                        >
+                            {"+"}
+                        </button>
+                    }
+                }
+            />
+            <For
+                each=move || inserts.get()
+                key=|insert| insert.clone()
+                children=move |(insert, name, count)| {
+                    let remove = insert.clone();
+                    view! {
+                        <button
+                            class="bg-zinc-800 hover:bg-zinc-600 px-3 py-1 rounded-lg cursor-pointer"
+                            on:click:target=move |_| {
+                                editor.edit(|print_file| {
+                                    let last = print_file.all().iter().rposition(|slot| {
+                                        matches!(slot, FilledCardSlot::Insert { insert } if *insert == remove)
+                                    });
+                                    if let Some(index) = last {
+                                        print_file.remove_card(index);
+                                    }
+                                });
+                            }
+                        >
+                            {"-"}
+                        </button>
+                        <p class="text-right font-bold">{count}</p>
+                        <p>{name}<span class="text-zinc-400">{" (insert)"}</span></p>
+                        <button
+                            class="bg-zinc-800 hover:bg-zinc-600 px-3 py-1 rounded-lg cursor-pointer"
+                            on:click:target=move |_| {
+                                editor.edit(|print_file| print_file.add_insert(insert.clone()));
+                            }
+                        >
+                            {"+"}
+                        </button>
+                    }
+                }
+            />
+        </div>
+    }
+}
+
+#[component]
+fn OpenDialog() -> impl IntoView {
+    let open_dialog = use_open_dialog();
+
+    let modal_ref = NodeRef::<Dialog>::new();
+    let _ = on_click_outside(modal_ref, move |_| open_dialog.set(None));
 
     move || {
         if let Some(open_dialog) = open_dialog.get() {
@@ -519,12 +1352,19 @@ fn OpenDialog() -> impl IntoView {
                         OpenDialog::Print => view! { <PrintContent /> }.into_any(),
                         OpenDialog::JnetImport => view! { <JnetImportContent /> }.into_any(),
                         OpenDialog::NrdbImport => view! { <NrdbImportContent /> }.into_any(),
+                        OpenDialog::DecklistExport => view! { <DecklistExportContent /> }.into_any(),
+                        OpenDialog::Project => view! { <ProjectContent /> }.into_any(),
+                        OpenDialog::AddSet => view! { <AddSetContent /> }.into_any(),
+                        OpenDialog::PrintingPreference => {
+                            view! { <PrintingPreferenceContent /> }.into_any()
+                        }
                         OpenDialog::TtsExport => view! { <TtsExportContent /> }.into_any(),
+                        OpenDialog::ImageExport => view! { <ImageExportContent /> }.into_any(),
                     }}
                 </dialog>
             }.into_any()
         } else {
-            view! {}.into_any()
+            ().into_any()
         }
     }
 }
@@ -533,7 +1373,7 @@ fn OpenDialog() -> impl IntoView {
 fn DialogContentCard() -> impl IntoView {
     let open_dialog = use_open_dialog();
     let (print_file, _) = use_print_file();
-    let (_, set_print_file) = use_print_file();
+    let editor = use_print_file_editor();
     let library_version = use_library_version();
 
     let card = Memo::new(move |_| {
@@ -575,15 +1415,13 @@ fn DialogContentCard() -> impl IntoView {
         else {
             return None;
         };
-        let Some(card_data) = with_library(|library| {
+        let card_data = with_library(|library| {
             library
                 .libraries
                 .get(&*print_group)
-                .and_then(|group| group.try_get_face_card(&face_id))
+                .and_then(|group| group.try_get_face_card(face_id))
                 .cloned()
-        }) else {
-            return None;
-        };
+        })?;
         let faces = match &card_data.alternate_face_data {
             AlternateFaceMetadata::Single => return None,
             AlternateFaceMetadata::Multiple(titles) => titles.len() + 1,
@@ -592,6 +1430,115 @@ fn DialogContentCard() -> impl IntoView {
         Some((face, face_id.clone(), faces))
     });
 
+    let printing_info = Memo::new(move |_| {
+        let _ = library_version.get();
+        let Some(FilledCardSlot::Card { printing }) = card.get() else {
+            return None;
+        };
+        let printings = with_library(|library| {
+            let group = library.libraries.get(&printing.print_group)?;
+            let card_data = group.try_get_face_card(&printing)?;
+            let printings = group
+                .card_printings(card_data)
+                .into_iter()
+                .filter_map(|meta| {
+                    card_data
+                        .printing_face(meta.id.id, printing.face_or_variant_specifier)
+                        .map(|face| (face.clone(), meta.printing_name.clone()))
+                })
+                .collect::<Vec<_>>();
+            Some(printings)
+        })?;
+        (printings.len() > 1).then_some((printing.id, printings))
+    });
+
+    let group_info = Memo::new(move |_| {
+        let _ = library_version.get();
+        let Some(FilledCardSlot::Card { printing }) = card.get() else {
+            return None;
+        };
+        let groups = with_library(|library| library.group_alternatives(&printing));
+        (groups.len() > 1).then_some((printing.print_group, groups))
+    });
+
+    let group_edit = move || {
+        let Some((current, groups)) = group_info.get() else {
+            return ().into_any();
+        };
+        view! {
+            <p class="font-bold">{"Print Group"}</p>
+            <div class="flex gap-2 flex-wrap">
+                <For
+                    each=move || groups.clone()
+                    key=|(group, _, _)| group.clone()
+                    children=move |(group, name, face)| {
+                        let selected = group == current;
+                        view! {
+                            <button
+                                class="hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                                class:bg-blue-800=selected
+                                class:bg-zinc-800=!selected
+                                on:click:target=move |_| {
+                                    if let Some(OpenDialog::Edit(index)) = open_dialog.get() {
+                                        let face = face.clone();
+                                        editor.edit(|print_file| print_file.update_card(index, face));
+                                    }
+                                }
+                            >
+                                {name}
+                            </button>
+                        }
+                    }
+                />
+            </div>
+        }
+        .into_any()
+    };
+
+    let printing_edit = move || {
+        let Some((current, printings)) = printing_info.get() else {
+            return ().into_any();
+        };
+        view! {
+            <p class="font-bold">{"Printing"}</p>
+            <div class="flex gap-2 flex-wrap">
+                <For
+                    each=move || printings.clone()
+                    key=|(face, _)| face.clone()
+                    children=move |(face, printing_name)| {
+                        let selected = face.id == current;
+                        let image_urls = FilledCardSlot::Card { printing: face.clone() }.thumbnail_urls();
+                        view! {
+                            <button
+                                class="hover:bg-zinc-600 p-2 rounded-lg cursor-pointer flex flex-col items-center gap-1 w-28"
+                                class:bg-blue-800=selected
+                                class:bg-zinc-800=!selected
+                                on:click:target=move |_| {
+                                    if let Some(OpenDialog::Edit(index)) = open_dialog.get() {
+                                        let face = face.clone();
+                                        editor.edit(|print_file| print_file.update_card(index, face));
+                                    }
+                                }
+                            >
+                                <img
+                                    class="w-24"
+                                    src=image_urls[0].clone()
+                                    alt=printing_name.clone()
+                                    on:load:target=move |ev| remember_image_mirror(&ev.target().src())
+                                    on:error:target=move |ev| {
+                                        next_fallback_image(&ev.target(), &image_urls);
+                                    }
+                                />
+                                <span class="text-sm text-balance">{printing_name.clone()}</span>
+                            </button>
+                        }
+                    }
+                />
+            </div>
+        }
+        .into_any()
+    };
+
     let face_edit = move || {
         let Some((face, face_id, faces)) = face_info.get() else {
             return ().into_any();
@@ -612,7 +1559,7 @@ fn DialogContentCard() -> impl IntoView {
                                 let mut new = face_id.clone();
                                 new.face_or_variant_specifier = Some(button_face);
                                 if let Some(OpenDialog::Edit(index)) = open_dialog.get() {
-                                    set_print_file.write().update_card(index, new);
+                                    editor.edit(|print_file| print_file.update_card(index, new));
                                 }
                             }
                         >
@@ -630,10 +1577,10 @@ fn DialogContentCard() -> impl IntoView {
 
     let close_ref = NodeRef::<Button>::new();
     Effect::new(move |_| {
-        if open_dialog.get().is_some() {
-            if let Some(close_ref) = close_ref.get() {
-                let _ = close_ref.focus();
-            }
+        if open_dialog.get().is_some()
+            && let Some(close_ref) = close_ref.get()
+        {
+            let _ = close_ref.focus();
         }
     });
 
@@ -662,9 +1609,8 @@ fn DialogContentCard() -> impl IntoView {
                 <button
                     class="bg-red-800 hover:bg-red-600 p-2 rounded-lg cursor-pointer"
                     on:click:target=move |_| {
-                        let (_, set_print_file) = use_print_file();
                         if let Some(OpenDialog::Edit(index)) = open_dialog.get() {
-                            set_print_file.write().remove_card(index);
+                            editor.edit(|print_file| print_file.remove_card(index));
                         };
                         open_dialog.set(None);
                     }
@@ -673,13 +1619,316 @@ fn DialogContentCard() -> impl IntoView {
                 </button>
                 {face_edit}
             </div>
+            {group_edit}
+            {printing_edit}
         }
         .into_any()
     }
 }
 
+/// The number of copies of a card a deck may contain.
+const PLAYSET: u32 = 3;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum SetQuantity {
+    /// As many copies as come in the product.
+    Packaged,
+    /// As many copies as come in the product, but no more than a playset.
+    Playset,
+    /// A single copy of each card.
+    Single,
+}
+impl SetQuantity {
+    /// Falls back to a playset for printings without a known quantity.
+    fn copies(self, quantity: Option<u32>) -> u32 {
+        match self {
+            SetQuantity::Packaged => quantity.unwrap_or(PLAYSET),
+            SetQuantity::Playset => quantity.unwrap_or(PLAYSET).min(PLAYSET),
+            SetQuantity::Single => 1,
+        }
+    }
+}
+impl std::fmt::Display for SetQuantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetQuantity::Packaged => "As Packaged".fmt(f),
+            SetQuantity::Playset => "Up to a Playset".fmt(f),
+            SetQuantity::Single => "One Each".fmt(f),
+        }
+    }
+}
+
+#[component]
+fn AddSetContent() -> impl IntoView {
+    let open_dialog = use_open_dialog();
+    let selected_library = use_selected_library();
+    let library_version = use_library_version();
+    let editor = use_print_file_editor();
+    let quantities = [
+        SetQuantity::Packaged,
+        SetQuantity::Playset,
+        SetQuantity::Single,
+    ];
+    let (set_quantity, set_set_quantity) = signal(SetQuantity::Playset);
+    let sets = Memo::new(move |_| {
+        let _ = library_version.get();
+        with_library(|library| {
+            library
+                .libraries
+                .get(&selected_library.get())
+                .map(Library::printing_names)
+                .unwrap_or_default()
+        })
+    });
+    view! {
+        <p class="text-lg font-bold">{"Add Set"}</p>
+        <div class="flex gap-2 items-center flex-wrap">
+            <div class="font-bold w-full md:w-[unset]">{"Copies"}</div>
+            <For
+                each=move || quantities
+                key=|quantity| *quantity
+                children=move |quantity| {
+                    let selected = Memo::new(move |_| set_quantity.get() == quantity);
+                    let not_selected = Memo::new(move |_| !selected.get());
+                    view! {
+                        <button
+                            class="p-2 rounded-lg cursor-pointer"
+                            class:bg-blue-800=selected
+                            class:hover:bg-zinc-600=not_selected
+                            class:bg-zinc-800=not_selected
+                            on:click:target=move |_| set_set_quantity.set(quantity)
+                        >
+                            {format!("{quantity}")}
+                        </button>
+                    }
+                }
+            />
+        </div>
+        <div class="flex gap-2 flex-wrap">
+            <For
+                each=move || sets.get()
+                key=|name| name.clone()
+                children=move |name| {
+                    let label = name.clone();
+                    view! {
+                        <button
+                            class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                            on:click:target=move |_| {
+                                let set_quantity = set_quantity.get_untracked();
+                                let contents = with_library(|library| {
+                                    let Some(group) = library.libraries.get(&selected_library.get())
+                                    else {
+                                        return Vec::new();
+                                    };
+                                    group
+                                        .set_contents(&name)
+                                        .into_iter()
+                                        .filter_map(|printing| {
+                                            let card = group.try_get_card(&printing.card_id)?;
+                                            let copies = set_quantity.copies(printing.quantity);
+                                            Some((card.clone(), printing.id.id, copies))
+                                        })
+                                        .collect::<Vec<_>>()
+                                });
+                                editor.edit(|print_file| {
+                                    for (card, printing, copies) in &contents {
+                                        for _ in 0..*copies {
+                                            print_file.add_card_printing(card, *printing);
+                                        }
+                                    }
+                                });
+                                open_dialog.set(None);
+                            }
+                        >
+                            {label}
+                        </button>
+                    }
+                }
+            />
+        </div>
+    }
+}
+
+#[component]
+fn PrintingPreferenceContent() -> impl IntoView {
+    let (printing_preference, set_printing_preference) = use_printing_preference();
+    let selected_library = use_selected_library();
+    let library_version = use_library_version();
+    let modes = [
+        PrintingPreference::Newest,
+        PrintingPreference::Oldest,
+        PrintingPreference::Ranked(Vec::new()),
+    ];
+    let ranked = Memo::new(move |_| match printing_preference.get() {
+        PrintingPreference::Ranked(ranked) => Some(ranked),
+        _ => None,
+    });
+    let all_sets = Memo::new(move |_| {
+        let _ = library_version.get();
+        with_library(|library| {
+            library
+                .libraries
+                .get(&selected_library.get())
+                .map(Library::printing_names)
+                .unwrap_or_default()
+        })
+    });
+    let available = Memo::new(move |_| {
+        let ranked = ranked.get().unwrap_or_default();
+        all_sets
+            .get()
+            .into_iter()
+            .filter(|name| !ranked.contains(name))
+            .collect::<Vec<_>>()
+    });
+    let editor = use_print_file_editor();
+    let (switch_result, set_switch_result) = signal(None::<String>);
+    let update_ranked = move |f: &dyn Fn(&mut Vec<String>)| {
+        set_printing_preference.update(|preference| {
+            if let PrintingPreference::Ranked(ranked) = preference {
+                f(ranked);
+            }
+        });
+    };
+    view! {
+        <p class="text-lg font-bold">{"Printings"}</p>
+        <p>{"Which printing to use for cards added from search or imported decks."}</p>
+        <div class="flex gap-2 items-center flex-wrap">
+            <For
+                each=move || modes.clone()
+                key=|mode| mode.to_string()
+                children=move |mode| {
+                    let label = mode.to_string();
+                    let selected = Memo::new({
+                        let mode = mode.clone();
+                        move |_| {
+                            std::mem::discriminant(&printing_preference.get())
+                                == std::mem::discriminant(&mode)
+                        }
+                    });
+                    let not_selected = Memo::new(move |_| !selected.get());
+                    view! {
+                        <button
+                            class="p-2 rounded-lg cursor-pointer"
+                            class:bg-blue-800=selected
+                            class:hover:bg-zinc-600=not_selected
+                            class:bg-zinc-800=not_selected
+                            on:click:target=move |_| {
+                                if !selected.get_untracked() {
+                                    set_printing_preference.set(mode.clone());
+                                }
+                            }
+                        >
+                            {label}
+                        </button>
+                    }
+                }
+            />
+        </div>
+        <Show when=move || ranked.get().is_some()>
+            <p class="font-bold">{"Preferred sets, most preferred first"}</p>
+            <ol class="flex flex-col gap-1">
+                <For
+                    each=move || ranked.get().unwrap_or_default().into_iter().enumerate()
+                    key=|entry| entry.clone()
+                    children=move |(i, name)| {
+                        view! {
+                            <li class="flex gap-2 items-center">
+                                <button
+                                    class="bg-zinc-800 hover:bg-zinc-600 px-2 rounded-lg cursor-pointer"
+                                    on:click:target=move |_| {
+                                        update_ranked(&|ranked| {
+                                            if i > 0 {
+                                                ranked.swap(i, i - 1);
+                                            }
+                                        });
+                                    }
+                                >
+                                    {"↑"}
+                                </button>
+                                <button
+                                    class="bg-zinc-800 hover:bg-zinc-600 px-2 rounded-lg cursor-pointer"
+                                    on:click:target=move |_| {
+                                        update_ranked(&|ranked| {
+                                            if i + 1 < ranked.len() {
+                                                ranked.swap(i, i + 1);
+                                            }
+                                        });
+                                    }
+                                >
+                                    {"↓"}
+                                </button>
+                                <button
+                                    class="bg-red-800 hover:bg-red-600 px-2 rounded-lg cursor-pointer"
+                                    on:click:target=move |_| {
+                                        update_ranked(&|ranked| {
+                                            ranked.remove(i);
+                                        });
+                                    }
+                                >
+                                    {"×"}
+                                </button>
+                                <span>{i + 1}{". "}{name}</span>
+                            </li>
+                        }
+                    }
+                />
+            </ol>
+            <p class="font-bold">{"Other sets"}</p>
+            <div class="flex gap-2 flex-wrap">
+                <For
+                    each=move || available.get()
+                    key=|name| name.clone()
+                    children=move |name| {
+                        let label = name.clone();
+                        view! {
+                            <button
+                                class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                                on:click:target=move |_| {
+                                    update_ranked(&|ranked| ranked.push(name.clone()));
+                                }
+                            >
+                                {"+ "}{label}
+                            </button>
+                        }
+                    }
+                />
+            </div>
+            <p class="text-zinc-400">{"Cards in none of these sets use their newest printing."}</p>
+        </Show>
+        <p class="text-lg font-bold">{"Switch Existing Cards"}</p>
+        <p>{"Move every card already in the print file that has a printing in a set to it."}</p>
+        <div class="flex gap-2 flex-wrap">
+            <For
+                each=move || all_sets.get()
+                key=|name| name.clone()
+                children=move |name| {
+                    let label = name.clone();
+                    view! {
+                        <button
+                            class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                            on:click:target=move |_| {
+                                let mut changed = 0;
+                                editor.edit(|print_file| changed = print_file.switch_printing(&name));
+                                set_switch_result.set(Some(match changed {
+                                    1 => format!("Switched 1 card to {name}"),
+                                    n => format!("Switched {n} cards to {name}"),
+                                }));
+                            }
+                        >
+                            {label}
+                        </button>
+                    }
+                }
+            />
+        </div>
+        {move || switch_result.get().map(|message| view! { <p class="font-bold">{message}</p> })}
+    }
+}
+
 #[component]
 fn PrintContent() -> impl IntoView {
+    let (print_file, _) = use_print_file();
     let (print_config, set_print_config) = use_print_config();
     let printing = use_printing();
     let sizes = [PrintSize::A4, PrintSize::UsLetter];
@@ -791,18 +2040,39 @@ fn PrintContent() -> impl IntoView {
                     class:bg-red-800=is_printing
                     disabled=is_printing
                     on:click:target=move |_| {
-                        do_print(printing);
+                        do_print(print_file, print_config, printing);
                     }
                 >
                     {print_message}
                 </button>
             </div>
+            <div class="flex gap-2 items-center flex-wrap">
+                <div class="font-bold w-full md:w-[unset]">{"Pick List"}</div>
+                <button
+                    class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                    on:click:target=move |_| {
+                        let csv = print_file.with(PrintFile::to_pick_list_csv);
+                        download_bytes(csv.as_bytes(), "pick-list.csv");
+                    }
+                >
+                    {"CSV"}
+                </button>
+                <button
+                    class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                    on:click:target=move |_| {
+                        do_pick_list_pdf(print_file, print_config);
+                    }
+                >
+                    {"PDF"}
+                </button>
+            </div>
         </div>
     }
 }
 
 #[component]
 fn TtsExportContent() -> impl IntoView {
+    let (print_file, _) = use_print_file();
     let printing = use_printing();
     let is_printing = Memo::new(move |_| printing.get());
     let is_not_printing = Memo::new(move |_| !is_printing.get());
@@ -842,11 +2112,138 @@ fn TtsExportContent() -> impl IntoView {
                     class:bg-red-800=is_printing
                     disabled=is_printing
                     on:click:target=move |_| {
-                        do_tts_export(CORP_TTS_BACK.to_string(), printing);
+                        do_tts_export(tts_back_urls("corp", CORP_TTS_BACK), print_file, printing);
+                    }
+                >
+                    {print_message_corp}
+                </button>
+                <button
+                    class="bg-green-800 hover:bg-green-600 p-2 rounded-lg cursor-pointer font-bold text-lg"
+                    class:bg-green-800=is_not_printing
+                    class:hover:bg-green-600=is_not_printing
+                    class:bg-red-800=is_printing
+                    disabled=is_printing
+                    on:click:target=move |_| {
+                        do_tts_export(tts_back_urls("runner", RUNNER_TTS_BACK), print_file, printing);
+                    }
+                >
+                    {print_message_runner}
+                </button>
+            </div>
+        </div>
+    }
+}
+
+#[derive(Debug, Copy, Default, Clone, Eq, Hash, PartialEq)]
+pub enum ImageExportFormat {
+    #[default]
+    Original,
+    Png,
+    Jpeg,
+}
+impl ImageExportFormat {
+    const fn extension(self) -> Option<&'static str> {
+        match self {
+            ImageExportFormat::Original => None,
+            ImageExportFormat::Png => Some("png"),
+            ImageExportFormat::Jpeg => Some("jpg"),
+        }
+    }
+}
+impl std::fmt::Display for ImageExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageExportFormat::Original => "Original".fmt(f),
+            ImageExportFormat::Png => "PNG".fmt(f),
+            ImageExportFormat::Jpeg => "JPEG".fmt(f),
+        }
+    }
+}
+
+#[component]
+fn ImageExportContent() -> impl IntoView {
+    let (print_file, _) = use_print_file();
+    let printing = use_printing();
+    let (format, set_format) = signal(ImageExportFormat::default());
+    let (bleed_mode, set_bleed_mode) = signal(BleedMode::None);
+    let formats = [
+        ImageExportFormat::Original,
+        ImageExportFormat::Png,
+        ImageExportFormat::Jpeg,
+    ];
+    let bleed_modes = [
+        BleedMode::None,
+        BleedMode::Narrow,
+        BleedMode::Medium,
+        BleedMode::Wide,
+    ];
+    let is_printing = Memo::new(move |_| printing.get());
+    let is_not_printing = Memo::new(move |_| !is_printing.get());
+    let reencoding = Memo::new(move |_| format.get() != ImageExportFormat::Original);
+    let print_message = Memo::new(move |_| {
+        if is_printing.get() {
+            "Generating..."
+        } else {
+            "Download Images"
+        }
+    });
+    view! {
+        <div class="flex flex-col gap-2 h-full justify-between">
+            <p class="text-lg font-bold">{"Images"}</p>
+            <p class="bg-blue-800 text-white font-bold px-2 py-1 max-w-max">
+                {"Every slot is exported as its own file, bundled in a zip."}
+            </p>
+            <div class="flex gap-2 items-center flex-wrap">
+                <div class="font-bold w-full md:w-[unset]">{"Format"}</div>
+                <For
+                    each=move || formats
+                    key=|format| *format
+                    children=move |button_format| {
+                        let selected = Memo::new(move |_| format.get() == button_format);
+                        let not_selected = Memo::new(move |_| !selected.get());
+                        view! {
+                            <button
+                                class="p-2 rounded-lg cursor-pointer"
+                                class:bg-blue-800=selected
+                                class:hover:bg-zinc-600=not_selected
+                                class:bg-zinc-800=not_selected
+                                on:click:target=move |_| {
+                                    set_format.set(button_format);
+                                }
+                            >
+                                {format!("{button_format}")}
+                            </button>
+                        }
                     }
-                >
-                    {print_message_corp}
-                </button>
+                />
+            </div>
+            <Show when=move || reencoding.get()>
+                <div class="flex gap-2 items-center flex-wrap">
+                    <div class="font-bold w-full md:w-[unset]">{"Bleed"}</div>
+                    <For
+                        each=move || bleed_modes
+                        key=|bleed| *bleed
+                        children=move |bleed| {
+                            let selected = Memo::new(move |_| bleed_mode.get() == bleed);
+                            let not_selected = Memo::new(move |_| !selected.get());
+                            view! {
+                                <button
+                                    class="p-2 rounded-lg cursor-pointer"
+                                    class:bg-blue-800=selected
+                                    class:hover:bg-zinc-600=not_selected
+                                    class:bg-zinc-800=not_selected
+                                    on:click:target=move |_| {
+                                        set_bleed_mode.set(bleed);
+                                    }
+                                >
+                                    {format!("{bleed}")}
+                                </button>
+                            }
+                        }
+                    />
+                </div>
+            </Show>
+            <div>
                 <button
                     class="bg-green-800 hover:bg-green-600 p-2 rounded-lg cursor-pointer font-bold text-lg"
                     class:bg-green-800=is_not_printing
@@ -854,10 +2251,15 @@ fn TtsExportContent() -> impl IntoView {
                     class:bg-red-800=is_printing
                     disabled=is_printing
                     on:click:target=move |_| {
-                        do_tts_export(RUNNER_TTS_BACK.to_string(), printing);
+                        let bleed_mode = if reencoding.get() {
+                            bleed_mode.get()
+                        } else {
+                            BleedMode::None
+                        };
+                        do_image_export(format.get(), bleed_mode, print_file, printing);
                     }
                 >
-                    {print_message_runner}
+                    {print_message}
                 </button>
             </div>
         </div>
@@ -867,7 +2269,9 @@ fn TtsExportContent() -> impl IntoView {
 #[component]
 fn JnetImportContent() -> impl IntoView {
     let (text_content, set_text_content) = signal(String::new());
-    let (_print_file, set_print_file) = use_print_file();
+    let editor = use_print_file_editor();
+    let (printing_preference, _) = use_printing_preference();
+    let selected_library = use_selected_library();
     view! {
         <p class="text-lg font-bold">{"JNET Import"}</p>
         <p class="bg-red-800 text-white font-bold px-2 py-1 w-max">
@@ -884,7 +2288,9 @@ fn JnetImportContent() -> impl IntoView {
             class="bg-blue-800 hover:bg-blue-600 p-2 rounded-lg cursor-pointer font-bold text-lg"
             on:click:target=move |_| {
                 let text_content = text_content.get();
-                'line: for line in text_content.lines() {
+                let printing_preference = printing_preference.get_untracked();
+                let selected_library = selected_library.get_untracked();
+                editor.edit(|print_file| 'line: for line in text_content.lines() {
                     let Some((count, name)) = line.split_once(" ") else {
                         console_warn(&format!("Invalid Line: {line}"));
                         continue;
@@ -894,22 +2300,22 @@ fn JnetImportContent() -> impl IntoView {
                         continue;
                     };
                     let cards = with_library(|library| {
-                        library.libraries["english"]
-                            .cards
-                            .values()
-                            .cloned()
-                            .collect::<Vec<_>>()
+                        library
+                            .libraries
+                            .get(&selected_library)
+                            .map(|library| library.cards.values().cloned().collect::<Vec<_>>())
+                            .unwrap_or_default()
                     });
                     for meta in cards {
                         if meta.title.title == name || meta.title.stripped_title == name {
                             for _ in 0..count {
-                                set_print_file.write().add_cards(&meta);
+                                print_file.add_cards(&meta, &printing_preference);
                             }
                             continue 'line;
                         }
                     }
                     console_warn(&format!("Card not found: {name}"));
-                }
+                });
             }
         >
             {"Import"}
@@ -920,6 +2326,9 @@ fn JnetImportContent() -> impl IntoView {
 #[component]
 fn NrdbImportContent() -> impl IntoView {
     let (text_content, set_text_content) = signal(String::new());
+    let editor = use_print_file_editor();
+    let (printing_preference, _) = use_printing_preference();
+    let selected_library = use_selected_library();
     let import_status = use_import_status();
     let open_dialog = use_open_dialog();
     view! {
@@ -932,7 +2341,14 @@ fn NrdbImportContent() -> impl IntoView {
             on:submit=move |ev| {
                 ev.prevent_default();
                 let text_content = text_content.get();
-                do_nrdb_import(&text_content, import_status, open_dialog);
+                do_nrdb_import(
+                    &text_content,
+                    editor,
+                    printing_preference,
+                    selected_library.get_untracked(),
+                    import_status,
+                    open_dialog,
+                );
             }
         >
             <input
@@ -950,11 +2366,321 @@ fn NrdbImportContent() -> impl IntoView {
     }
 }
 
+#[derive(Debug, Copy, Default, Clone, Eq, Hash, PartialEq)]
+pub enum DecklistExportFormat {
+    #[default]
+    Jnet,
+    Nrdb,
+    Checklist,
+}
+impl DecklistExportFormat {
+    const fn file_name(self) -> &'static str {
+        match self {
+            DecklistExportFormat::Jnet => "decklist.txt",
+            DecklistExportFormat::Nrdb => "decklist.json",
+            DecklistExportFormat::Checklist => "checklist.txt",
+        }
+    }
+}
+impl std::fmt::Display for DecklistExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecklistExportFormat::Jnet => "JNET".fmt(f),
+            DecklistExportFormat::Nrdb => "NRDB".fmt(f),
+            DecklistExportFormat::Checklist => "Checklist".fmt(f),
+        }
+    }
+}
+
+#[component]
+fn DecklistExportContent() -> impl IntoView {
+    let (print_file, _) = use_print_file();
+    let library_version = use_library_version();
+    let (format, set_format) = signal(DecklistExportFormat::default());
+    let formats = [
+        DecklistExportFormat::Jnet,
+        DecklistExportFormat::Nrdb,
+        DecklistExportFormat::Checklist,
+    ];
+    let UseClipboardReturn {
+        is_supported: clipboard_supported,
+        copied,
+        copy,
+        ..
+    } = use_clipboard();
+
+    let text_content = Memo::new(move |_| {
+        let _ = library_version.get();
+        print_file.with(|print_file| match format.get() {
+            DecklistExportFormat::Jnet => print_file.to_jnet_text(),
+            DecklistExportFormat::Nrdb => print_file.to_nrdb_json(),
+            DecklistExportFormat::Checklist => print_file.to_checklist(),
+        })
+    });
+    let copy_message = Memo::new(move |_| if copied.get() { "Copied!" } else { "Copy" });
+
+    view! {
+        <p class="text-lg font-bold">{"Export Decklist"}</p>
+        <p class="bg-red-800 text-white font-bold px-2 py-1 w-max">
+            {"Inserts are not included!"}
+        </p>
+        <div class="flex gap-2 items-center flex-wrap">
+            <For
+                each=move || formats
+                key=|format| *format
+                children=move |button_format| {
+                    let selected = Memo::new(move |_| format.get() == button_format);
+                    let not_selected = Memo::new(move |_| !selected.get());
+                    view! {
+                        <button
+                            class="p-2 rounded-lg cursor-pointer"
+                            class:bg-blue-800=selected
+                            class:hover:bg-zinc-600=not_selected
+                            class:bg-zinc-800=not_selected
+                            on:click:target=move |_| {
+                                set_format.set(button_format);
+                            }
+                        >
+                            {format!("{button_format}")}
+                        </button>
+                    }
+                }
+            />
+        </div>
+        <textarea
+            class="bg-zinc-900 border-1 border-white p-2 rounded-md min-h-48 font-mono"
+            readonly
+            prop:value=move || text_content.get()
+        />
+        <div class="flex gap-2">
+            <Show when=move || clipboard_supported.get()>
+                <button
+                    class="bg-blue-800 hover:bg-blue-600 p-2 rounded-lg cursor-pointer font-bold text-lg"
+                    on:click:target={
+                        let copy = copy.clone();
+                        move |_| copy(&text_content.get())
+                    }
+                >
+                    {copy_message}
+                </button>
+            </Show>
+            <button
+                class="bg-green-800 hover:bg-green-600 p-2 rounded-lg cursor-pointer font-bold text-lg"
+                on:click:target=move |_| {
+                    download_bytes(text_content.get().as_bytes(), format.get().file_name());
+                }
+            >
+                {"Download"}
+            </button>
+        </div>
+    }
+}
+
+fn format_timestamp(millis: u64) -> String {
+    #[allow(clippy::cast_precision_loss)]
+    let date = js_sys::Date::new(&JsValue::from_f64(millis as f64));
+    date.to_locale_string("default", &JsValue::UNDEFINED).into()
+}
+
+#[component]
+fn ProjectContent() -> impl IntoView {
+    let (print_file, _) = use_print_file();
+    let (print_config, _) = use_print_config();
+    let (project_list, set_project_list) = use_project_list();
+    let (active_project, _) = use_active_project();
+    let switcher = use_project_switcher();
+    let open_dialog = use_open_dialog();
+    let (error, set_error) = signal(None::<String>);
+    let file_input = NodeRef::<Input>::new();
+
+    let projects = Memo::new(move |_| {
+        let mut projects = project_list.with(|list| list.all().to_vec());
+        projects.sort_by_key(|project| std::cmp::Reverse(project.modified));
+        projects
+    });
+
+    let UseClipboardReturn {
+        is_supported: clipboard_supported,
+        copied,
+        copy,
+        ..
+    } = use_clipboard();
+    let copy_message = Memo::new(move |_| {
+        if copied.get() {
+            "Copied!"
+        } else {
+            "Copy Share Link"
+        }
+    });
+
+    let save = move |_| {
+        let project = ProjectFile::new(print_file.get(), print_config.get());
+        let name = active_project
+            .get()
+            .and_then(|id| project_list.with(|list| list.get(id).map(|p| p.name.clone())))
+            .unwrap_or_else(|| "project".to_string());
+        match project.to_ron() {
+            Ok(text) => download_bytes(text.as_bytes(), &format!("{name}.ron")),
+            Err(err) => set_error.set(Some(format!("Failed to save project: {err}"))),
+        }
+    };
+    let open = move |_| {
+        let Some(input) = file_input.get() else {
+            return;
+        };
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        // Otherwise choosing the same file again, such as after fixing it,
+        // would not fire another change.
+        input.set_value("");
+        spawn_local(async move {
+            let text = match JsFuture::from(file.text()).await {
+                Ok(text) => text.as_string().unwrap_or_default(),
+                Err(_) => {
+                    set_error.set(Some("Failed to read project file".to_string()));
+                    return;
+                }
+            };
+            match ProjectFile::from_ron(&text) {
+                Ok(project) => {
+                    let name = file.name();
+                    let name = name.strip_suffix(".ron").unwrap_or(&name).to_string();
+                    switcher.create(name, project.print_file, project.print_config);
+                    set_error.set(None);
+                    open_dialog.set(None);
+                }
+                Err(err) => set_error.set(Some(err.to_string())),
+            }
+        });
+    };
+
+    view! {
+        <p class="text-lg font-bold">{"Projects"}</p>
+        <Show when=move || error.get().is_some()>
+            <p class="bg-amber-500 text-black font-bold px-2 py-1 max-w-max">
+                {move || error.get().unwrap_or_default()}
+            </p>
+        </Show>
+        <div class="flex flex-col gap-2 max-h-96 overflow-y-auto">
+            <For
+                each=move || projects.get()
+                key=|project| (project.id, project.name.clone(), project.modified)
+                children=move |project| {
+                    let id = project.id;
+                    let is_active = Memo::new(move |_| active_project.get() == Some(id));
+                    let is_not_active = Memo::new(move |_| !is_active.get());
+                    view! {
+                        <div class="flex gap-2 items-center flex-wrap">
+                            <input
+                                type="text"
+                                class="bg-zinc-900 border-1 border-white py-1 px-2 rounded-md grow"
+                                prop:value=project.name.clone()
+                                on:change:target=move |ev| {
+                                    let name = ev.target().value();
+                                    set_project_list.update(|list| list.rename(id, name, now()));
+                                }
+                            />
+                            <span class="text-sm text-zinc-400">
+                                {format_timestamp(project.modified)}
+                            </span>
+                            <button
+                                class="p-2 rounded-lg"
+                                class:bg-blue-800=is_active
+                                class:bg-zinc-800=is_not_active
+                                class:hover:bg-zinc-600=is_not_active
+                                class:cursor-pointer=is_not_active
+                                disabled=is_active
+                                on:click:target=move |_| {
+                                    switcher.switch(id);
+                                }
+                            >
+                                {move || if is_active.get() { "Current" } else { "Open" }}
+                            </button>
+                            <button
+                                class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                                on:click:target=move |_| {
+                                    set_project_list.update(|list| {
+                                        list.duplicate(id, now());
+                                    });
+                                }
+                            >
+                                {"Duplicate"}
+                            </button>
+                            <button
+                                class="bg-red-800 hover:bg-red-600 p-2 rounded-lg cursor-pointer"
+                                on:click:target=move |_| {
+                                    switcher.delete(id);
+                                }
+                            >
+                                {"Delete"}
+                            </button>
+                        </div>
+                    }
+                }
+            />
+        </div>
+        <div class="flex gap-2 flex-wrap">
+            <button
+                class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer font-bold text-lg"
+                on:click:target=move |_| {
+                    switcher.create(
+                        "Untitled project".to_string(),
+                        PrintFile::default(),
+                        PrintConfig::default(),
+                    );
+                }
+            >
+                {"New Project"}
+            </button>
+            <button
+                class="bg-green-800 hover:bg-green-600 p-2 rounded-lg cursor-pointer font-bold text-lg"
+                on:click:target=save
+            >
+                {"Save to File"}
+            </button>
+            <button
+                class="bg-blue-800 hover:bg-blue-600 p-2 rounded-lg cursor-pointer font-bold text-lg"
+                on:click:target=move |_| {
+                    if let Some(input) = file_input.get() {
+                        input.click();
+                    }
+                }
+            >
+                {"Open from File"}
+            </button>
+            <Show when=move || clipboard_supported.get()>
+                <button
+                    class="bg-blue-800 hover:bg-blue-600 p-2 rounded-lg cursor-pointer font-bold text-lg"
+                    on:click:target={
+                        let copy = copy.clone();
+                        move |_| {
+                            let link = print_file
+                                .with(|print_file| share_link(print_file, print_config.get()));
+                            copy(&link);
+                        }
+                    }
+                >
+                    {copy_message}
+                </button>
+            </Show>
+            <input
+                type="file"
+                accept=".ron"
+                class="hidden"
+                node_ref=file_input
+                on:change:target=open
+            />
+        </div>
+    }
+}
+
 #[component]
 fn ControlConfig() -> impl IntoView {
     let open_dialog = use_open_dialog();
-    let (_, set_print_file) = use_print_file();
+    let editor = use_print_file_editor();
     let (print_file, _) = use_print_file();
+    let (decklist_layout, set_decklist_layout) = use_decklist_layout();
 
     let used_slots = Memo::new(move |_| print_file.read().len());
     let total_pages = Memo::new(move |_| used_slots.get().div_ceil(9));
@@ -971,6 +2697,62 @@ fn ControlConfig() -> impl IntoView {
             <p class="font-bold text-lg">{"Proxy.NRO"}</p>
             <div class="flex flex-wrap gap-2 items-center">
                 <p>{used_slots}{"c "}{total_pages}{"p ("}{overflow}{")"}</p>
+                <button
+                    class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer disabled:opacity-50 disabled:cursor-not-allowed"
+                    title="Undo (Ctrl+Z)"
+                    prop:disabled=move || !editor.history.read().can_undo()
+                    on:click:target=move |_| editor.undo()
+                >
+                    {"Undo"}
+                </button>
+                <button
+                    class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer disabled:opacity-50 disabled:cursor-not-allowed"
+                    title="Redo (Ctrl+Shift+Z)"
+                    prop:disabled=move || !editor.history.read().can_redo()
+                    on:click:target=move |_| editor.redo()
+                >
+                    {"Redo"}
+                </button>
+                <button
+                    class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                    on:click:target=move |_| {
+                        set_decklist_layout.update(|layout| {
+                            *layout = match layout {
+                                DecklistLayout::Grid => DecklistLayout::List,
+                                DecklistLayout::List => DecklistLayout::Grid,
+                            };
+                        });
+                    }
+                >
+                    {move || match decklist_layout.get() {
+                        DecklistLayout::Grid => "List View",
+                        DecklistLayout::List => "Grid View",
+                    }}
+                </button>
+                <button
+                    class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                    on:click:target=move |_| {
+                        open_dialog.set(Some(OpenDialog::Project));
+                    }
+                >
+                    {"Projects"}
+                </button>
+                <button
+                    class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                    on:click:target=move |_| {
+                        open_dialog.set(Some(OpenDialog::AddSet));
+                    }
+                >
+                    {"Sets"}
+                </button>
+                <button
+                    class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                    on:click:target=move |_| {
+                        open_dialog.set(Some(OpenDialog::PrintingPreference));
+                    }
+                >
+                    {"Printings"}
+                </button>
                 <button
                     class="bg-green-800 hover:bg-green-600 p-2 rounded-lg cursor-pointer"
                     on:click:target=move |_| {
@@ -987,6 +2769,14 @@ fn ControlConfig() -> impl IntoView {
                 >
                     {"TTS"}
                 </button>
+                <button
+                    class="bg-green-800 hover:bg-green-600 p-2 rounded-lg cursor-pointer"
+                    on:click:target=move |_| {
+                        open_dialog.set(Some(OpenDialog::ImageExport));
+                    }
+                >
+                    {"Images"}
+                </button>
                 <button
                     class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
                     on:click:target=move |_| {
@@ -1003,10 +2793,18 @@ fn ControlConfig() -> impl IntoView {
                 >
                     {"NRDB"}
                 </button>
+                <button
+                    class="bg-zinc-800 hover:bg-zinc-600 p-2 rounded-lg cursor-pointer"
+                    on:click:target=move |_| {
+                        open_dialog.set(Some(OpenDialog::DecklistExport));
+                    }
+                >
+                    {"Export"}
+                </button>
                 <button
                     class="bg-red-800 hover:bg-red-600 p-2 rounded-lg cursor-pointer"
                     on:click:target=move |_| {
-                        set_print_file.write().clear();
+                        editor.edit(PrintFile::clear);
                     }
                 >
                     {"Clear"}
@@ -1016,46 +2814,63 @@ fn ControlConfig() -> impl IntoView {
     }
 }
 
+#[allow(clippy::cast_possible_truncation)]
+fn download_bytes(bytes: &[u8], file_name: &str) {
+    let js_bytes = Uint8Array::new_with_length(bytes.len() as u32);
+    js_bytes.copy_from(bytes);
+    let js_array = JsValue::from(Box::new([js_bytes]) as Box<[_]>);
+    let js_bytes_blob = Blob::new_with_buffer_source_sequence(&js_array).expect("blob");
+    let link = document()
+        .create_element("a")
+        .expect("element")
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .expect("anchor");
+    let url = Url::create_object_url_with_blob(&js_bytes_blob).expect("url");
+    link.set_href(&url);
+    link.set_download(file_name);
+    let body = document().body().expect("body");
+    let cld = body.append_child(&link).expect("append");
+    link.click();
+    body.remove_child(&cld).expect("remove");
+    Url::revoke_object_url(&url).expect("revoke");
+}
+
 const CORP_TTS_BACK: &str = "https://nro-public.s3.nl-ams.scw.cloud/voluntary/public-assets/custom-assets/tts_card_backs/tts_corp_back.png";
 const RUNNER_TTS_BACK: &str = "https://nro-public.s3.nl-ams.scw.cloud/voluntary/public-assets/custom-assets/tts_card_backs/tts_runner_back.png";
 
-fn do_tts_export(back: String, printing: Subfield<Store<AppState>, AppState, bool>) {
-    printing.set(true);
-    let (print_file, _) = use_print_file();
+/// Where to download the card back that fills the rest of a TTS export from:
+/// the local override when there is one, then the public back in case the
+/// override cannot be loaded.
+fn tts_back_urls(side: &str, default: &str) -> Vec<String> {
+    with_library(|library| library.local_back_image_url(side).map(str::to_string))
+        .into_iter()
+        .chain(std::iter::once(default.to_string()))
+        .collect()
+}
+
+fn do_tts_export(
+    back_urls: Vec<String>,
+    print_file: Signal<PrintFile>,
+    printing: Subfield<Store<AppState>, AppState, bool>,
+) {
     let print_file = print_file.read();
 
-    spawn_local(async move {
-        let files_to_download = print_file
-            .all()
-            .iter()
-            .take(69)
-            .map(FilledCardSlot::image_url)
-            .chain(std::iter::once(back.clone()))
-            .collect::<HashSet<_>>();
-        let mut downloaded_files = files_to_download
-            .into_iter()
-            .map(|url| async move {
-                let request_url = normalize_request_url(&url);
-                let bytes = reqwest::get(&request_url)
-                    .await
-                    .expect("Cannot Download")
-                    .bytes()
-                    .await
-                    .expect("Cannot get bytes");
-                let image = ImageReader::new(Cursor::new(bytes))
-                    .with_guessed_format()
-                    .expect("Cannot guess format")
-                    .decode()
-                    .expect("cannot decode");
-                (url, image)
-            })
-            .collect::<FuturesUnordered<_>>()
-            .collect::<HashMap<String, DynamicImage>>()
-            .await;
+    spawn_export(printing, async move {
+        // A sheet holds 70 cards, and the last is always the back.
+        let print_slots = &print_file.all()[..print_file.len().min(69)];
+        let mut downloaded_files = HashMap::new();
+        for (slot, downloaded) in download_slot_images(print_slots).await? {
+            let image = decode_image(&downloaded.bytes)?;
+            downloaded_files.insert(slot, image);
+        }
         for image in downloaded_files.values_mut() {
             *image = image.resize_exact(405, 567, image::imageops::FilterType::CatmullRom);
         }
-        let print_slots = print_file.all();
+        let back_image = decode_image(&download_first(&back_urls).await?.bytes)?.resize_exact(
+            405,
+            567,
+            image::imageops::FilterType::CatmullRom,
+        );
         let height = (print_slots.len() as u32 + 1).div_ceil(10);
         let mut output = DynamicImage::new(4050, height * 567, image::ColorType::Rgba8);
         let mut row = 0;
@@ -1063,7 +2878,7 @@ fn do_tts_export(back: String, printing: Subfield<Store<AppState>, AppState, boo
         for (i, slot) in print_slots.iter().enumerate() {
             column = i % 10;
             row = i / 10;
-            let slot_image = &downloaded_files[&slot.image_url()];
+            let slot_image = &downloaded_files[slot];
             overlay(
                 &mut output,
                 slot_image,
@@ -1076,11 +2891,10 @@ fn do_tts_export(back: String, printing: Subfield<Store<AppState>, AppState, boo
             column = 0;
             row += 1;
         }
-        let back_image = &downloaded_files[&back];
         for column in column..10 {
             overlay(
                 &mut output,
-                back_image,
+                &back_image,
                 column as i64 * 405,
                 row as i64 * 567,
             );
@@ -1090,35 +2904,102 @@ fn do_tts_export(back: String, printing: Subfield<Store<AppState>, AppState, boo
         output
             .write_to(&mut output_bytes, image::ImageFormat::Png)
             .expect("Cannot write to bytes");
-        let output_bytes = output_bytes.into_inner();
-        let js_bytes = Uint8Array::new_with_length(output_bytes.len() as u32);
-        js_bytes.copy_from(&output_bytes);
-        let js_array = JsValue::from(Box::new([js_bytes]) as Box<[_]>);
-        let js_bytes_blob = Blob::new_with_buffer_source_sequence(&js_array).expect("blob");
-        let link = document()
-            .create_element("a")
-            .expect("element")
-            .dyn_into::<web_sys::HtmlAnchorElement>()
-            .expect("anchor");
-        let url = Url::create_object_url_with_blob(&js_bytes_blob).expect("url");
-        link.set_href(&url);
-        link.set_download("proxies.pdf");
-        let body = document().body().expect("body");
-        let cld = body.append_child(&link).expect("append");
-        link.click();
-        body.remove_child(&cld).expect("remove");
-        Url::revoke_object_url(&url).expect("revoke");
-        printing.set(false);
+        download_bytes(&output_bytes.into_inner(), "proxies.pdf");
+        Ok(())
+    });
+}
+
+/// Width of a card image in millimetres, used to convert bleed into pixels.
+const CARD_IMAGE_WIDTH_MM: f32 = 63.5;
+
+/// Characters that are not allowed in file names on at least one major OS.
+const INVALID_FILE_NAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+fn image_export_file_name(
+    index: usize,
+    total: usize,
+    slot: &FilledCardSlot,
+    extension: &str,
+) -> String {
+    let width = total.to_string().len().max(2);
+    let name = match slot.printing_name() {
+        Some(printing_name) => format!("{} ({printing_name})", slot.name()),
+        None => slot.name(),
+    };
+    let name = name.replace(INVALID_FILE_NAME_CHARS, "-");
+    format!("{number:0>width$} - {name}.{extension}", number = index + 1)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+#[allow(clippy::cast_precision_loss)]
+fn add_bleed(image: &DynamicImage, bleed_mode: BleedMode) -> DynamicImage {
+    let bleed = (bleed_mode.bleed() * image.width() as f32 / CARD_IMAGE_WIDTH_MM).round() as u32;
+    if bleed == 0 {
+        return image.clone();
+    }
+    let source = image.to_rgba8();
+    let (width, height) = source.dimensions();
+    let output = image::RgbaImage::from_fn(width + bleed * 2, height + bleed * 2, |x, y| {
+        let x = x.saturating_sub(bleed).min(width - 1);
+        let y = y.saturating_sub(bleed).min(height - 1);
+        *source.get_pixel(x, y)
+    });
+    DynamicImage::ImageRgba8(output)
+}
+
+fn do_image_export(
+    format: ImageExportFormat,
+    bleed_mode: BleedMode,
+    print_file: Signal<PrintFile>,
+    printing: Subfield<Store<AppState>, AppState, bool>,
+) {
+    let print_file = print_file.read();
+
+    spawn_export(printing, async move {
+        let print_slots = print_file.all();
+        let downloaded_files = download_slot_images(print_slots).await?;
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (i, slot) in print_slots.iter().enumerate() {
+            let downloaded = &downloaded_files[slot];
+            let (extension, contents) = match format.extension() {
+                None => (
+                    downloaded.extension().unwrap_or("webp"),
+                    downloaded.bytes.clone(),
+                ),
+                Some(extension) => {
+                    let image = add_bleed(&decode_image(&downloaded.bytes)?, bleed_mode);
+                    let mut contents = Cursor::new(Vec::new());
+                    match format {
+                        ImageExportFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+                            .write_to(&mut contents, image::ImageFormat::Jpeg),
+                        _ => image.write_to(&mut contents, image::ImageFormat::Png),
+                    }
+                    .expect("Cannot write to bytes");
+                    (extension, contents.into_inner())
+                }
+            };
+            let name = image_export_file_name(i, print_slots.len(), slot, extension);
+            zip.start_file(name, options)
+                .expect("Cannot start zip entry");
+            zip.write_all(&contents).expect("Cannot write zip entry");
+        }
+        let zip_bytes = zip.finish().expect("Cannot finish zip").into_inner();
+        download_bytes(&zip_bytes, "proxies.zip");
+        Ok(())
     });
 }
 
 fn do_nrdb_import(
     from: &str,
+    editor: PrintFileEditor,
+    printing_preference: Signal<PrintingPreference>,
+    selected_library: String,
     import_status: Subfield<Store<AppState>, AppState, Option<ImportStatus>>,
     open_dialog: Subfield<Store<AppState>, AppState, Option<OpenDialog>>,
 ) {
-    let (_print_file, set_print_file) = use_print_file();
-
     import_status.set(Some(ImportStatus::Importing));
 
     let public_list = Regex::new(r#"deck\/view\/([0-9a-f-]+)"#).unwrap();
@@ -1162,7 +3043,7 @@ fn do_nrdb_import(
             import_status.set(Some(ImportStatus::Failed));
             return;
         };
-        let Some(deck) = data.get(0) else {
+        let Some(deck) = data.first() else {
             console_error("JSON `data` is empty");
             import_status.set(Some(ImportStatus::Failed));
             return;
@@ -1177,6 +3058,7 @@ fn do_nrdb_import(
             import_status.set(Some(ImportStatus::Failed));
             return;
         };
+        let mut imported = Vec::new();
         'nrdb_card: for (card, count) in cards {
             let Some(count) = count.as_u64() else {
                 console_error("JSON `deck.cards` value is not a number");
@@ -1197,22 +3079,28 @@ fn do_nrdb_import(
             });
             console_log(&format!("Importing {count} {nrdb_printing}"));
             let cards = with_library(|library| {
-                library.libraries["english"]
-                    .cards
-                    .values()
-                    .cloned()
-                    .collect::<Vec<_>>()
+                library
+                    .libraries
+                    .get(&selected_library)
+                    .map(|library| library.cards.values().cloned().collect::<Vec<_>>())
+                    .unwrap_or_default()
             });
             for card in cards {
                 if card.printings.iter().any(|printing| printing.id == nrdb_printing) {
                     for _ in 0..count {
-                        set_print_file.write().add_cards(&card);
+                        imported.push(card.clone());
                     }
                     continue 'nrdb_card;
                 }
             }
             console_warn(&format!("Cannot find {nrdb_printing}"));
         }
+        let printing_preference = printing_preference.get_untracked();
+        editor.edit(|print_file| {
+            for card in &imported {
+                print_file.add_cards(card, &printing_preference);
+            }
+        });
         import_status.set(None);
         open_dialog.set(None);
     });
@@ -1220,42 +3108,26 @@ fn do_nrdb_import(
 
 #[allow(clippy::too_many_lines)]
 #[allow(clippy::cast_possible_truncation)]
-fn do_print(printing: Subfield<Store<AppState>, AppState, bool>) {
-    printing.set(true);
-    let (print_file, _) = use_print_file();
-    let (print_config, _) = use_print_config();
+fn do_print(
+    print_file: Signal<PrintFile>,
+    print_config: Signal<PrintConfig>,
+    printing: Subfield<Store<AppState>, AppState, bool>,
+) {
     let print_file = print_file.read();
     let print_config = print_config.get();
 
-    spawn_local(async move {
+    spawn_export(printing, async move {
         let mut doc = PdfDocument::new("proxies");
-        let files_to_download = print_file
-            .all()
-            .iter()
-            .map(FilledCardSlot::image_url)
-            .collect::<HashSet<_>>();
-        let downloaded_files = files_to_download
-            .into_iter()
-            .map(|url| async move {
-                let request_url = normalize_request_url(&url);
-                let bytes = reqwest::get(&request_url)
-                    .await
-                    .expect("Cannot Download")
-                    .bytes()
-                    .await
-                    .expect("Cannot get bytes");
-                let mut errs = Vec::new();
-                let image = RawImage::decode_from_bytes_async(&bytes, &mut errs)
-                    .await
-                    .expect("cannot decode");
-                (url, image)
-            })
-            .collect::<FuturesUnordered<_>>()
-            .collect::<HashMap<String, RawImage>>()
-            .await;
+        let mut downloaded_files = HashMap::new();
+        for (slot, downloaded) in download_slot_images(print_file.all()).await? {
+            let mut errs = Vec::new();
+            let image = RawImage::decode_from_bytes_async(&downloaded.bytes, &mut errs).await?;
+            downloaded_files.insert(slot, image);
+        }
 
-        let mut page_ops: Vec<Vec<Op>> = vec![vec![]; print_file.all().len().div_ceil(9)];
-        let transforms = (0..9)
+        let mut page_ops: Vec<Vec<Op>> =
+            vec![vec![]; print_file.all().len().div_ceil(SLOTS_PER_PAGE)];
+        let transforms = (0..SLOTS_PER_PAGE)
             .map(|slot| {
                 let (x, y, scale) = print_config.slot(slot);
                 XObjectTransform {
@@ -1299,15 +3171,13 @@ fn do_print(printing: Subfield<Store<AppState>, AppState, bool>) {
             })
             .collect::<Vec<_>>();
         for (i, slot) in print_file.all().iter().enumerate() {
-            let page_index = (i + 1).div_ceil(9) - 1;
-            let page_slot = i % 9;
-            let url = slot.image_url();
-            let id = doc.add_image(&downloaded_files[&url]);
+            let position = SlotPosition::of(i);
+            let id = doc.add_image(&downloaded_files[slot]);
             let object = Op::UseXobject {
                 id,
-                transform: transforms[page_slot],
+                transform: transforms[position.page_slot()],
             };
-            page_ops[page_index].push(object);
+            page_ops[position.page].push(object);
         }
         for page in &mut page_ops {
             page.extend(marks.clone());
@@ -1332,27 +3202,120 @@ fn do_print(printing: Subfield<Store<AppState>, AppState, bool>) {
             },
             &mut vec![],
         );
-        let js_bytes = Uint8Array::new_with_length(pdf_bytes.len() as u32);
-        js_bytes.copy_from(&pdf_bytes);
-        let js_array = JsValue::from(Box::new([js_bytes]) as Box<[_]>);
-        let js_bytes_blob = Blob::new_with_buffer_source_sequence(&js_array).expect("blob");
-        let link = document()
-            .create_element("a")
-            .expect("element")
-            .dyn_into::<web_sys::HtmlAnchorElement>()
-            .expect("anchor");
-        let url = Url::create_object_url_with_blob(&js_bytes_blob).expect("url");
-        link.set_href(&url);
-        link.set_download("proxies.pdf");
-        let body = document().body().expect("body");
-        let cld = body.append_child(&link).expect("append");
-        link.click();
-        body.remove_child(&cld).expect("remove");
-        Url::revoke_object_url(&url).expect("revoke");
-        printing.set(false);
+        download_bytes(&pdf_bytes, "proxies.pdf");
+        Ok(())
     });
 }
 
+const PICK_LIST_MARGIN: f32 = 15.0;
+const PICK_LIST_LINE_HEIGHT: f32 = 5.0;
+const PICK_LIST_FONT_SIZE: f32 = 9.0;
+/// Offsets of the pick list columns from the left margin, in millimetres.
+const PICK_LIST_COLUMNS: [f32; 6] = [0.0, 12.0, 22.0, 34.0, 112.0, 162.0];
+/// Space left between a cell and the next column, in millimetres.
+const PICK_LIST_COLUMN_GAP: f32 = 2.0;
+/// The width of a wide Helvetica character as a fraction of the font size.
+/// The built-in fonts cannot be measured, so cells are cut to fit with this.
+const PICK_LIST_CHAR_WIDTH: f32 = 0.6;
+
+/// Fits a cell into `width` millimetres. The built-in fonts can only show
+/// WinAnsi, so other characters are replaced.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn pick_list_cell(cell: &str, width: f32) -> String {
+    let cell = cell
+        .chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' => '\'',
+            '\u{201C}' | '\u{201D}' => '"',
+            '\u{2013}' | '\u{2014}' => '-',
+            ' '..='~' | '\u{A0}'..='\u{FF}' => c,
+            _ => '?',
+        })
+        .collect::<String>();
+    let char_width = Mm::from(Pt(PICK_LIST_FONT_SIZE)).0 * PICK_LIST_CHAR_WIDTH;
+    let max_chars = (width / char_width).max(0.0) as usize;
+    if cell.chars().count() <= max_chars {
+        return cell;
+    }
+    let mut cell = cell
+        .chars()
+        .take(max_chars.saturating_sub(3))
+        .collect::<String>();
+    cell.push_str("...");
+    cell
+}
+
+fn pick_list_row(y: f32, page_width: f32, font: BuiltinFont, cells: [String; 6]) -> Vec<Op> {
+    let mut ops = vec![Op::StartTextSection];
+    let ends = PICK_LIST_COLUMNS
+        .into_iter()
+        .skip(1)
+        .map(|x| x - PICK_LIST_COLUMN_GAP)
+        .chain(std::iter::once(page_width - PICK_LIST_MARGIN * 2.0));
+    for ((x, end), cell) in PICK_LIST_COLUMNS.into_iter().zip(ends).zip(cells) {
+        let cell = pick_list_cell(&cell, end - x);
+        ops.push(Op::SetTextCursor {
+            pos: Point::new(Mm(PICK_LIST_MARGIN + x), Mm(y)),
+        });
+        ops.push(Op::SetFontSizeBuiltinFont {
+            size: Pt(PICK_LIST_FONT_SIZE),
+            font,
+        });
+        ops.push(Op::WriteTextBuiltinFont {
+            items: vec![TextItem::Text(cell)],
+            font,
+        });
+    }
+    ops.push(Op::EndTextSection);
+    ops
+}
+
+fn do_pick_list_pdf(print_file: Signal<PrintFile>, print_config: Signal<PrintConfig>) {
+    let pick_list = print_file.with(PrintFile::pick_list);
+    let (page_width, page_height) = print_config.get().paper();
+
+    let header = || ["Page", "Row", "Col", "Title", "Printing", "Face"].map(str::to_string);
+    let top = page_height - PICK_LIST_MARGIN;
+    let mut pages = vec![];
+    let mut ops = pick_list_row(top, page_width, BuiltinFont::HelveticaBold, header());
+    let mut y = top - PICK_LIST_LINE_HEIGHT;
+    let mut last_page = None;
+    for entry in pick_list {
+        // Leave a gap between sheets so each pile is easy to spot.
+        let new_sheet = last_page.is_some_and(|page| page != entry.position.page);
+        if new_sheet {
+            y -= PICK_LIST_LINE_HEIGHT / 2.0;
+        }
+        last_page = Some(entry.position.page);
+        if y < PICK_LIST_MARGIN {
+            pages.push(PdfPage::new(Mm(page_width), Mm(page_height), ops));
+            ops = pick_list_row(top, page_width, BuiltinFont::HelveticaBold, header());
+            y = top - PICK_LIST_LINE_HEIGHT;
+        }
+        ops.extend(pick_list_row(
+            y,
+            page_width,
+            BuiltinFont::Helvetica,
+            [
+                (entry.position.page + 1).to_string(),
+                (entry.position.row + 1).to_string(),
+                (entry.position.column + 1).to_string(),
+                entry.title,
+                entry.printing_name.unwrap_or_default(),
+                entry.face.unwrap_or_default(),
+            ],
+        ));
+        y -= PICK_LIST_LINE_HEIGHT;
+    }
+    pages.push(PdfPage::new(Mm(page_width), Mm(page_height), ops));
+
+    let pdf_bytes = PdfDocument::new("pick list")
+        .with_pages(pages)
+        .save(&PdfSaveOptions::default(), &mut vec![]);
+    download_bytes(&pdf_bytes, "pick-list.pdf");
+}
+
 // bg-zinc-700
 // ring-4
 // bg-zinc-800
@@ -7,8 +7,9 @@ use std::{
 use anyhow::Context;
 use clap::Parser;
 use proxy_elev::{
-    AlternateFaceMetadata, CardFacePrintingId, CardId, CardMetadata, InsertId, InsertMetadata,
-    Library, LocalImageOverride, MultiLibrary, PrintingMetadata, Title,
+    AlternateFaceMetadata, CardDetails, CardFacePrintingId, CardId, CardMetadata, ImageUrlTemplate,
+    InsertId, InsertMetadata, Library, LocalImageOverride, LocalInsertImageOverride, ManifestIndex,
+    MultiLibrary, PrintingMetadata, Title, encode_manifest,
 };
 use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize};
@@ -26,7 +27,7 @@ struct Opt {
     /// Optional local overlay output path (defaults to `local-assets/manifest.local.ron` when local manifest is present)
     #[arg(long)]
     local_output: Option<PathBuf>,
-    /// Location to output the built artifact to
+    /// Directory to output the per-group manifests and their index to
     output: PathBuf,
 }
 
@@ -40,6 +41,8 @@ struct ManifestExtras {
     local_image: Vec<LocalImageOverrideInput>,
     #[serde(default)]
     local_image_root: Option<LocalImageRootInput>,
+    #[serde(default)]
+    image_mirrors: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,6 +59,8 @@ struct ExtraCard {
     group: Option<String>,
     printing_name: Option<String>,
     printing_id: Option<u32>,
+    quantity: Option<u32>,
+    pack: Option<String>,
     #[serde(default)]
     printings: Vec<ExtraPrinting>,
     #[serde(default)]
@@ -67,6 +72,8 @@ struct ExtraCard {
 struct ExtraPrinting {
     id: u32,
     name: Option<String>,
+    quantity: Option<u32>,
+    pack: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -77,7 +84,11 @@ struct NrdbRemap {
 
 #[derive(Debug, Deserialize, Clone)]
 struct LocalImageOverrideInput {
-    id: u32,
+    /// The printing to override, unless this overrides an insert or card back
+    id: Option<u32>,
+    insert: Option<String>,
+    /// `corp` or `runner`
+    back: Option<String>,
     #[serde(default)]
     face: Option<usize>,
     #[serde(default)]
@@ -98,6 +109,42 @@ struct StableMultiLibrary {
     collection_names: BTreeMap<String, String>,
     nrdb_remap: BTreeMap<u32, u32>,
     local_images: Vec<LocalImageOverride>,
+    local_insert_images: Vec<LocalInsertImageOverride>,
+    local_back_images: BTreeMap<String, String>,
+    fallbacks: BTreeMap<String, String>,
+    image_urls: BTreeMap<String, ImageUrlTemplate>,
+    image_mirrors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct StableManifestIndex {
+    collection_names: BTreeMap<String, String>,
+    nrdb_remap: BTreeMap<u32, u32>,
+    local_images: Vec<LocalImageOverride>,
+    local_insert_images: Vec<LocalInsertImageOverride>,
+    local_back_images: BTreeMap<String, String>,
+    fallbacks: BTreeMap<String, String>,
+    image_urls: BTreeMap<String, ImageUrlTemplate>,
+    image_mirrors: Vec<String>,
+    revision: u64,
+}
+
+impl StableMultiLibrary {
+    /// Splits off the index so that each group's library can be written to its own file.
+    fn split(self, revision: u64) -> (StableManifestIndex, BTreeMap<String, StableLibrary>) {
+        let index = StableManifestIndex {
+            collection_names: self.collection_names,
+            nrdb_remap: self.nrdb_remap,
+            local_images: self.local_images,
+            local_insert_images: self.local_insert_images,
+            local_back_images: self.local_back_images,
+            fallbacks: self.fallbacks,
+            image_urls: self.image_urls,
+            image_mirrors: self.image_mirrors,
+            revision,
+        };
+        (index, self.libraries)
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -156,6 +203,8 @@ impl From<&MultiLibrary> for StableMultiLibrary {
                 .then_with(|| a.face_or_variant_specifier.cmp(&b.face_or_variant_specifier))
                 .then_with(|| a.url.cmp(&b.url))
         });
+        let mut local_insert_images = value.local_insert_images.clone();
+        local_insert_images.sort_by(|a, b| a.insert.cmp(&b.insert).then_with(|| a.url.cmp(&b.url)));
 
         Self {
             libraries: value
@@ -174,6 +223,23 @@ impl From<&MultiLibrary> for StableMultiLibrary {
                 .map(|(k, v)| (*k, *v))
                 .collect(),
             local_images,
+            local_insert_images,
+            local_back_images: value
+                .local_back_images
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            fallbacks: value
+                .fallbacks
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            image_urls: value
+                .image_urls
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            image_mirrors: value.image_mirrors.clone(),
         }
     }
 }
@@ -192,12 +258,12 @@ fn insert_printing_face(
     card_meta: &mut CardMetadata,
     card_id: &CardId,
     print_group: &str,
-    printing_id: u32,
+    printing: &ExtraPrinting,
     face_or_variant_specifier: Option<usize>,
     printing_name: &str,
 ) {
     let face = CardFacePrintingId {
-        id: printing_id,
+        id: printing.id,
         face_or_variant_specifier,
         print_group: print_group.to_string(),
     };
@@ -208,6 +274,8 @@ fn insert_printing_face(
             id: face,
             card_id: card_id.clone(),
             printing_name: printing_name.to_string(),
+            quantity: printing.quantity,
+            pack: printing.pack.clone(),
         },
     );
 }
@@ -230,13 +298,13 @@ fn build_extra_by_group(
                 card.id
             );
         }
-        if let Some(variants) = card.variants {
-            if variants < 2 {
-                anyhow::bail!(
-                    "Extra card `{}` has `variants` < 2; omit it for single-face cards",
-                    card.id
-                );
-            }
+        if let Some(variants) = card.variants
+            && variants < 2
+        {
+            anyhow::bail!(
+                "Extra card `{}` has `variants` < 2; omit it for single-face cards",
+                card.id
+            );
         }
 
         if card.printing_id.is_some() && !card.printings.is_empty() {
@@ -245,6 +313,12 @@ fn build_extra_by_group(
                 card.id
             );
         }
+        if (card.quantity.is_some() || card.pack.is_some()) && !card.printings.is_empty() {
+            anyhow::bail!(
+                "Extra card `{}` must set `quantity` and `pack` on each of its `printings`",
+                card.id
+            );
+        }
 
         let mut printings = card.printings.clone();
         if printings.is_empty() {
@@ -260,6 +334,8 @@ fn build_extra_by_group(
             printings.push(ExtraPrinting {
                 id: printing_id,
                 name: card.printing_name.clone(),
+                quantity: card.quantity,
+                pack: card.pack.clone(),
             });
         }
 
@@ -300,17 +376,17 @@ fn build_extra_by_group(
             alternate_face_data,
             id: card_id.clone(),
             printings: BTreeSet::new(),
+            details: CardDetails::default(),
         };
 
-        if let Some(existing) = base_library.libraries[&group].cards.get(&card_id) {
-            if existing.title != card_meta.title
-                || existing.alternate_face_data != card_meta.alternate_face_data
-            {
-                anyhow::bail!(
-                    "Extra card `{}` conflicts with existing card metadata",
-                    card.id
-                );
-            }
+        if let Some(existing) = base_library.libraries[&group].cards.get(&card_id)
+            && (existing.title != card_meta.title
+                || existing.alternate_face_data != card_meta.alternate_face_data)
+        {
+            anyhow::bail!(
+                "Extra card `{}` conflicts with existing card metadata",
+                card.id
+            );
         }
 
         for printing in printings {
@@ -325,7 +401,7 @@ fn build_extra_by_group(
                     &mut card_meta,
                     &card_id,
                     &group,
-                    printing.id,
+                    &printing,
                     Some(1),
                     &printing_name,
                 );
@@ -335,7 +411,7 @@ fn build_extra_by_group(
                         &mut card_meta,
                         &card_id,
                         &group,
-                        printing.id,
+                        &printing,
                         Some(i + 2),
                         &printing_name,
                     );
@@ -347,7 +423,7 @@ fn build_extra_by_group(
                         &mut card_meta,
                         &card_id,
                         &group,
-                        printing.id,
+                        &printing,
                         Some(variant),
                         &printing_name,
                     );
@@ -358,7 +434,7 @@ fn build_extra_by_group(
                     &mut card_meta,
                     &card_id,
                     &group,
-                    printing.id,
+                    &printing,
                     None,
                     &printing_name,
                 );
@@ -376,7 +452,7 @@ fn build_extra_by_group(
                         card.id
                     );
                 }
-                existing.printings.extend(card_meta.printings.into_iter());
+                existing.printings.extend(card_meta.printings);
             }
             std::collections::hash_map::Entry::Vacant(entry) => {
                 entry.insert(card_meta);
@@ -402,12 +478,16 @@ fn merge_extra_cards(
     Ok(())
 }
 
+/// Card back sides that can be given a local image.
+const CARD_BACK_SIDES: [&str; 2] = ["corp", "runner"];
+
 fn build_local_image_overrides(
     base_library: &MultiLibrary,
     extras: &ManifestExtras,
-) -> anyhow::Result<Vec<LocalImageOverride>> {
+    overlay: &mut MultiLibrary,
+) -> anyhow::Result<()> {
     if extras.local_image.is_empty() {
-        return Ok(Vec::new());
+        return Ok(());
     }
 
     let root_url = extras
@@ -421,29 +501,74 @@ fn build_local_image_overrides(
         .and_then(|root| root.path.as_ref())
         .cloned();
 
-    let mut overrides = Vec::with_capacity(extras.local_image.len());
     for override_ in &extras.local_image {
+        let label = match (override_.id, &override_.insert, &override_.back) {
+            (Some(id), None, None) => id.to_string(),
+            (None, Some(insert), None) => format!("insert `{insert}`"),
+            (None, None, Some(back)) => format!("`{back}` card back"),
+            _ => {
+                anyhow::bail!("Local image overrides need exactly one of `id`, `insert` or `back`")
+            }
+        };
         if override_.url.is_none()
             && override_.path.is_none()
             && root_url.is_none()
             && root_path.is_none()
         {
             anyhow::bail!(
-                "Local image override {} missing `url` or `path` and no `local_image_root`",
-                override_.id
+                "Local image override {label} missing `url` or `path` and no `local_image_root`"
             );
         }
         if override_.url.is_some() && override_.path.is_some() {
-            anyhow::bail!(
-                "Local image override {} cannot define both `url` and `path`",
-                override_.id
-            );
+            anyhow::bail!("Local image override {label} cannot define both `url` and `path`");
         }
 
         let group = override_
             .group
             .clone()
             .unwrap_or_else(|| "english".to_string());
+
+        if let Some(back) = &override_.back {
+            if !CARD_BACK_SIDES.contains(&back.as_str()) {
+                anyhow::bail!("Local image override {label} is not one of {CARD_BACK_SIDES:?}");
+            }
+            let url = local_image_url(
+                override_,
+                root_url.as_deref(),
+                root_path.as_deref(),
+                &format!("{back}-back.webp"),
+            );
+            overlay.local_back_images.insert(back.clone(), url);
+            continue;
+        }
+
+        if let Some(name) = &override_.insert {
+            let insert = InsertId {
+                name: name.clone(),
+                print_group: group.clone(),
+            };
+            let exists = base_library
+                .libraries
+                .get(&group)
+                .context(format!("Local image override group `{group}` missing"))?
+                .inserts
+                .contains_key(&insert);
+            if !exists {
+                anyhow::bail!("Local image override {label} does not exist in group `{group}`");
+            }
+            let url = local_image_url(
+                override_,
+                root_url.as_deref(),
+                root_path.as_deref(),
+                &format!("{name}.webp"),
+            );
+            overlay
+                .local_insert_images
+                .push(LocalInsertImageOverride { insert, url });
+            continue;
+        }
+
+        let id = override_.id.expect("checked above");
         let face = override_.face;
 
         let matches: Vec<CardFacePrintingId> = base_library
@@ -452,14 +577,14 @@ fn build_local_image_overrides(
             .context(format!("Local image override group `{group}` missing"))?
             .faces
             .keys()
-            .filter(|printing| printing.id == override_.id)
+            .filter(|printing| printing.id == id)
             .cloned()
             .collect();
 
         if matches.is_empty() {
             anyhow::bail!(
                 "Local image override {} does not match any printings in group `{}`",
-                override_.id,
+                id,
                 group
             );
         }
@@ -468,7 +593,7 @@ fn build_local_image_overrides(
             if !matches.iter().any(|printing| printing.face_or_variant_specifier == Some(face)) {
                 anyhow::bail!(
                     "Local image override {} face {} does not exist in group `{}`",
-                    override_.id,
+                    id,
                     face,
                     group
                 );
@@ -479,51 +604,65 @@ fn build_local_image_overrides(
         } else {
             anyhow::bail!(
                 "Local image override {} matches multiple faces; specify `face`",
-                override_.id
+                id
             );
         };
 
         let file_name = match face_specifier {
-            Some(face) => format!("{}.{}.webp", override_.id, face),
-            None => format!("{}.webp", override_.id),
-        };
-
-        let url = if let Some(url) = override_.url.clone() {
-            url
-        } else if let Some(path) = override_.path.clone() {
-            let path = path.replace('\\', "/");
-            if path.starts_with("file://") {
-                path
-            } else {
-                format!("file:///{path}")
-            }
-        } else if let Some(root_url) = root_url.as_ref() {
-            let base = root_url.trim_end_matches('/');
-            format!("{base}/{file_name}")
-        } else {
-            let root = root_path
-                .as_ref()
-                .expect("root_path checked above")
-                .replace('\\', "/")
-                .trim_end_matches('/')
-                .to_string();
-            let path = format!("{root}/{file_name}");
-            if path.starts_with("file://") {
-                path
-            } else {
-                format!("file:///{path}")
-            }
+            Some(face) => format!("{}.{}.webp", id, face),
+            None => format!("{}.webp", id),
         };
-
-        overrides.push(LocalImageOverride {
-            id: override_.id,
+        let url = local_image_url(
+            override_,
+            root_url.as_deref(),
+            root_path.as_deref(),
+            &file_name,
+        );
+
+        overlay.local_images.push(LocalImageOverride {
+            id,
             face_or_variant_specifier: face_specifier,
             print_group: group,
             url,
         });
     }
 
-    Ok(overrides)
+    Ok(())
+}
+
+/// Where to load an override from: its own `url` or `path`, or else `file_name` under the
+/// `local_image_root`.
+fn local_image_url(
+    override_: &LocalImageOverrideInput,
+    root_url: Option<&str>,
+    root_path: Option<&str>,
+    file_name: &str,
+) -> String {
+    if let Some(url) = override_.url.clone() {
+        url
+    } else if let Some(path) = override_.path.clone() {
+        let path = path.replace('\\', "/");
+        if path.starts_with("file://") {
+            path
+        } else {
+            format!("file:///{path}")
+        }
+    } else if let Some(root_url) = root_url {
+        let base = root_url.trim_end_matches('/');
+        format!("{base}/{file_name}")
+    } else {
+        let root = root_path
+            .expect("root_path checked above")
+            .replace('\\', "/")
+            .trim_end_matches('/')
+            .to_string();
+        let path = format!("{root}/{file_name}");
+        if path.starts_with("file://") {
+            path
+        } else {
+            format!("file:///{path}")
+        }
+    }
 }
 
 #[allow(clippy::too_many_lines)]
@@ -539,6 +678,11 @@ fn main() -> anyhow::Result<()> {
         collection_names: HashMap::new(),
         nrdb_remap: HashMap::new(),
         local_images: Vec::new(),
+        local_insert_images: Vec::new(),
+        local_back_images: HashMap::new(),
+        fallbacks: HashMap::new(),
+        image_urls: HashMap::new(),
+        image_mirrors: Vec::new(),
     };
 
     let manifest = std::fs::read_to_string(&opt.manifest)?;
@@ -577,9 +721,12 @@ fn main() -> anyhow::Result<()> {
             .as_array()
             .context("`printing` not array")?;
 
-        let manifest_inserts = manifest_collection["insert"]
-            .as_array()
-            .context("`insert` not array")?;
+        let manifest_inserts = manifest_collection
+            .get("insert")
+            .map(|inserts| inserts.as_array().context("`insert` not array"))
+            .transpose()?
+            .cloned()
+            .unwrap_or_default();
 
         let manifest_group = manifest_collection["group"]
             .as_str()
@@ -589,13 +736,41 @@ fn main() -> anyhow::Result<()> {
             .collection_names
             .insert(manifest_group.into(), collection_name.into());
 
+        if let Some(fallback) = manifest_collection.get("fallback") {
+            let fallback = fallback.as_str().context("`fallback` not a string")?;
+            multi_library
+                .fallbacks
+                .insert(manifest_group.into(), fallback.into());
+        }
+
+        if let Some(image_url) = manifest_collection.get("image_url") {
+            let card = image_url.as_str().context("`image_url` not a string")?;
+            let insert = manifest_collection
+                .get("insert_image_url")
+                .map(|url| url.as_str().context("`insert_image_url` not a string"))
+                .transpose()?;
+            let extension = manifest_collection
+                .get("image_extension")
+                .map(|ext| ext.as_str().context("`image_extension` not a string"))
+                .transpose()?
+                .unwrap_or("webp");
+            multi_library.image_urls.insert(
+                manifest_group.into(),
+                ImageUrlTemplate {
+                    card: card.into(),
+                    insert: insert.map(Into::into),
+                    extension: extension.into(),
+                },
+            );
+        }
+
         let mut library = Library {
             cards: HashMap::new(),
             faces: HashMap::new(),
             inserts: HashMap::new(),
         };
 
-        for insert in manifest_inserts {
+        for insert in &manifest_inserts {
             let insert_id = insert["id"].as_str().context("`id` not a string")?;
             let insert_title = insert["title"].as_str().context("`title` not a string")?;
             let insert_stripped_title = insert
@@ -671,6 +846,22 @@ fn main() -> anyhow::Result<()> {
                     .map(|faces| faces.as_array().context("`faces` not array"))
                     .transpose()?;
 
+                let quantity = printing
+                    .get("quantity")
+                    .map(|quantity| quantity.as_u64().context("`quantity` not a number"))
+                    .transpose()?
+                    .map(u32::try_from)
+                    .transpose()?;
+
+                let pack = printing
+                    .get("card_set_id")
+                    .map(|pack| {
+                        pack.as_str()
+                            .context("`card_set_id` not a string")
+                            .map(str::to_string)
+                    })
+                    .transpose()?;
+
                 let library_entry = library.cards.entry(card_id.clone());
                 match library_entry {
                     std::collections::hash_map::Entry::Occupied(mut occupied_entry) => {
@@ -692,6 +883,8 @@ fn main() -> anyhow::Result<()> {
                                     id: face,
                                     card_id: card_id.clone(),
                                     printing_name: manifest_name.into(),
+                                    quantity,
+                                    pack: pack.clone(),
                                 },
                             );
                             for (face, _) in faces.iter().enumerate() {
@@ -707,6 +900,8 @@ fn main() -> anyhow::Result<()> {
                                         id: face,
                                         card_id: card_id.clone(),
                                         printing_name: manifest_name.into(),
+                                        quantity,
+                                        pack: pack.clone(),
                                     },
                                 );
                             }
@@ -723,6 +918,8 @@ fn main() -> anyhow::Result<()> {
                                     id: face,
                                     card_id,
                                     printing_name: manifest_name.into(),
+                                    quantity,
+                                    pack: pack.clone(),
                                 },
                             );
                         }
@@ -753,6 +950,41 @@ fn main() -> anyhow::Result<()> {
                             stripped_title: card_stripped_title.into(),
                         };
 
+                        let code = |key: &str| {
+                            card_data
+                                .get(key)
+                                .map(|code| {
+                                    code.as_str()
+                                        .with_context(|| format!("`{key}` not a string"))
+                                        .map(str::to_string)
+                                })
+                                .transpose()
+                        };
+                        let subtypes = card_data
+                            .get("card_subtype_ids")
+                            .map(|subtypes| {
+                                subtypes
+                                    .as_array()
+                                    .context("`card_subtype_ids` not array")?
+                                    .iter()
+                                    .map(|subtype| {
+                                        subtype
+                                            .as_str()
+                                            .context("`card_subtype_ids` entry not a string")
+                                            .map(str::to_string)
+                                    })
+                                    .collect::<anyhow::Result<Vec<_>>>()
+                            })
+                            .transpose()?
+                            .unwrap_or_default();
+                        let details = CardDetails {
+                            faction: code("faction_id")?,
+                            side: code("side_id")?,
+                            card_type: code("card_type_id")?,
+                            subtypes,
+                            text: code("stripped_text")?.or(code("text")?),
+                        };
+
                         let card_faces = card_data
                             .get("faces")
                             .map(|faces| faces.as_array().context("`faces` not array"))
@@ -775,6 +1007,8 @@ fn main() -> anyhow::Result<()> {
                                         id: face,
                                         card_id: card_id.clone(),
                                         printing_name: manifest_name.into(),
+                                        quantity,
+                                        pack: pack.clone(),
                                     },
                                 );
                                 for (i, face) in card_faces.iter().enumerate() {
@@ -804,6 +1038,8 @@ fn main() -> anyhow::Result<()> {
                                             id: face,
                                             card_id: card_id.clone(),
                                             printing_name: manifest_name.into(),
+                                            quantity,
+                                            pack: pack.clone(),
                                         },
                                     );
                                 }
@@ -814,6 +1050,7 @@ fn main() -> anyhow::Result<()> {
                                     ),
                                     id: card_id.clone(),
                                     printings,
+                                    details,
                                 }
                             }
                             // Single card
@@ -829,6 +1066,8 @@ fn main() -> anyhow::Result<()> {
                                         id: face.clone(),
                                         card_id: card_id.clone(),
                                         printing_name: manifest_name.into(),
+                                        quantity,
+                                        pack: pack.clone(),
                                     },
                                 );
                                 CardMetadata {
@@ -836,6 +1075,7 @@ fn main() -> anyhow::Result<()> {
                                     alternate_face_data: AlternateFaceMetadata::Single,
                                     id: card_id.clone(),
                                     printings: BTreeSet::from([face.clone()]),
+                                    details,
                                 }
                             }
                             // Variant card, such as matryoshka
@@ -853,6 +1093,8 @@ fn main() -> anyhow::Result<()> {
                                         id: face,
                                         card_id: card_id.clone(),
                                         printing_name: manifest_name.into(),
+                                        quantity,
+                                        pack: pack.clone(),
                                     },
                                 );
 
@@ -869,6 +1111,8 @@ fn main() -> anyhow::Result<()> {
                                             id: face,
                                             card_id: card_id.clone(),
                                             printing_name: manifest_name.into(),
+                                            quantity,
+                                            pack: pack.clone(),
                                         },
                                     );
                                 }
@@ -879,6 +1123,7 @@ fn main() -> anyhow::Result<()> {
                                     ),
                                     id: card_id.clone(),
                                     printings,
+                                    details,
                                 }
                             }
                         };
@@ -888,11 +1133,48 @@ fn main() -> anyhow::Result<()> {
             }
         }
 
+        // Localized titles for collections such as fan translations, which
+        // reuse the NRDB printings of the English cards.
+        if let Some(titles) = manifest_collection.get("title") {
+            for title in titles.as_array().context("`title` not array")? {
+                let card_id = title["id"].as_str().context("`id` not a string")?;
+                let localized = title["title"].as_str().context("`title` not a string")?;
+                let stripped_title = title
+                    .get("stripped_title")
+                    .map(|title| {
+                        title
+                            .as_str()
+                            .context("`stripped_title` not a string")
+                            .map(str::to_string)
+                    })
+                    .transpose()?
+                    .unwrap_or_else(|| strip_non_ascii(localized));
+                let card = library
+                    .cards
+                    .get_mut(&CardId(card_id.to_string()))
+                    .with_context(|| {
+                        format!("Localized title for `{card_id}` has no card in `{manifest_group}`")
+                    })?;
+                card.title = Title {
+                    title: localized.into(),
+                    stripped_title,
+                };
+            }
+        }
+
         multi_library
             .libraries
             .insert(manifest_group.into(), library);
     }
 
+    for (group, fallback) in &multi_library.fallbacks {
+        if !multi_library.libraries.contains_key(fallback) {
+            anyhow::bail!("Collection `{group}` falls back to unknown group `{fallback}`");
+        }
+    }
+
+    multi_library.image_mirrors = extras.image_mirrors;
+
     if !extras.card.is_empty() {
         merge_extra_cards(
             &mut multi_library,
@@ -929,7 +1211,8 @@ fn main() -> anyhow::Result<()> {
     let local_overlay = if let Some(local_extras) = local_extras {
         let has_local = !local_extras.card.is_empty()
             || !local_extras.nrdb_remap.is_empty()
-            || !local_extras.local_image.is_empty();
+            || !local_extras.local_image.is_empty()
+            || !local_extras.image_mirrors.is_empty();
         if !has_local {
             None
         } else {
@@ -945,12 +1228,23 @@ fn main() -> anyhow::Result<()> {
                 collection_names: HashMap::new(),
                 nrdb_remap: HashMap::new(),
                 local_images: Vec::new(),
+                local_insert_images: Vec::new(),
+                local_back_images: HashMap::new(),
+                fallbacks: HashMap::new(),
+                image_urls: HashMap::new(),
+                image_mirrors: local_extras.image_mirrors.clone(),
             };
 
             for (group, library) in &extra_by_group {
                 if let Some(name) = multi_library.collection_names.get(group) {
                     overlay.collection_names.insert(group.clone(), name.clone());
                 }
+                if let Some(fallback) = multi_library.fallbacks.get(group) {
+                    overlay.fallbacks.insert(group.clone(), fallback.clone());
+                }
+                if let Some(template) = multi_library.image_urls.get(group) {
+                    overlay.image_urls.insert(group.clone(), template.clone());
+                }
                 overlay.libraries.insert(group.clone(), library.clone());
             }
 
@@ -990,7 +1284,7 @@ fn main() -> anyhow::Result<()> {
                 overlay.nrdb_remap = remap;
             }
 
-            overlay.local_images = build_local_image_overrides(&validation_library, &local_extras)?;
+            build_local_image_overrides(&validation_library, &local_extras, &mut overlay)?;
 
             Some(overlay)
         }
@@ -998,27 +1292,60 @@ fn main() -> anyhow::Result<()> {
         None
     };
 
-    std::fs::create_dir_all(opt.output.parent().unwrap())?;
-
-    let mut write = std::fs::File::options()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(opt.output)?;
+    // The client embeds the index and fetches each group's library when it is first used. It
+    // loads the binary files, and the RON files are kept for reviewing changes.
+    std::fs::create_dir_all(&opt.output)?;
+    let (mut index, libraries) = StableMultiLibrary::from(&multi_library).split(0);
+    let libraries = libraries
+        .into_iter()
+        .map(|(group, library)| {
+            let buf = ron::ser::to_string_pretty(&library, PrettyConfig::default())?;
+            Ok((group, library, buf))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    // Only move the revision on when the manifest actually changed, so that rerunning `prepare`
+    // does not make clients download an identical manifest.
+    let same_as_file = |path: PathBuf, buf: &str| {
+        std::fs::read_to_string(path).is_ok_and(|previous| previous == buf)
+    };
+    let previous_revision = std::fs::read_to_string(opt.output.join("index.ron"))
+        .ok()
+        .and_then(|index| ron::de::from_str::<ManifestIndex>(&index).ok())
+        .map(|index| index.revision);
+    index.revision = previous_revision.unwrap_or_default();
+    let index_buf = ron::ser::to_string_pretty(&index, PrettyConfig::default())?;
+    let unchanged = previous_revision.is_some()
+        && same_as_file(opt.output.join("index.ron"), &index_buf)
+        && libraries
+            .iter()
+            .all(|(group, _, buf)| same_as_file(opt.output.join(format!("{group}.ron")), buf));
+    if !unchanged {
+        index.revision = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+    }
 
-    let stable_manifest = StableMultiLibrary::from(&multi_library);
-    let buf = ron::ser::to_string_pretty(&stable_manifest, PrettyConfig::default())?;
-    write.write_all(buf.as_bytes())?;
+    let buf = ron::ser::to_string_pretty(&index, PrettyConfig::default())?;
+    std::fs::write(opt.output.join("index.ron"), buf)?;
+    std::fs::write(opt.output.join("index.bin"), encode_manifest(&index))?;
+    for (group, library, buf) in libraries {
+        std::fs::write(opt.output.join(format!("{group}.ron")), buf)?;
+        std::fs::write(
+            opt.output.join(format!("{group}.bin")),
+            encode_manifest(&library),
+        )?;
+    }
 
     if let Some(local_overlay) = local_overlay {
         let local_output = opt
             .local_output
             .clone()
             .unwrap_or_else(|| PathBuf::from("local-assets/manifest.local.ron"));
-        if let Some(parent) = local_output.parent() {
-            if !parent.as_os_str().is_empty() {
-                std::fs::create_dir_all(parent)?;
-            }
+        if let Some(parent) = local_output.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
         }
         let mut write = std::fs::File::options()
             .write(true)
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Parser;
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType};
+
+/// Generate the thumbnails shown in the decklist from full size card images.
+#[derive(Parser, Debug)]
+struct Opt {
+    /// Directory of full size images, such as `english/card`
+    files: PathBuf,
+    /// Width of the thumbnails in pixels
+    #[arg(long, default_value_t = 250)]
+    width: u32,
+    /// JPEG quality of the thumbnails, from 1 to 100
+    #[arg(long, default_value_t = 80, value_parser = clap::value_parser!(u8).range(1..=100))]
+    quality: u8,
+    /// Regenerate thumbnails even if they are newer than their image
+    #[arg(long)]
+    force: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let opt = Opt::parse();
+
+    let out_dir = opt.files.join("thumb");
+    std::fs::create_dir_all(&out_dir)?;
+
+    for file in std::fs::read_dir(&opt.files)? {
+        let file = file?.path();
+        if file.extension().is_none_or(|ext| ext != "webp") {
+            continue;
+        }
+        // Only the `.webp` is replaced, keeping face suffixes such as `.2`.
+        let mut name = file.file_stem().context("`file_stem` missing")?.to_owned();
+        name.push(".jpg");
+        let out_file = out_dir.join(name);
+
+        if !opt.force
+            && let Ok(out_modified) = std::fs::metadata(&out_file).and_then(|m| m.modified())
+            && out_modified >= std::fs::metadata(&file)?.modified()?
+        {
+            continue;
+        }
+
+        let image =
+            image::open(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+        // Thumbnails are lossy to keep lists quick to load, and have no
+        // transparency to keep.
+        let thumbnail = image
+            .resize(opt.width, u32::MAX, FilterType::Lanczos3)
+            .into_rgb8();
+        let write = std::fs::File::create(&out_file)
+            .with_context(|| format!("Failed to write {}", out_file.display()))?;
+        thumbnail
+            .write_with_encoder(JpegEncoder::new_with_quality(
+                std::io::BufWriter::new(write),
+                opt.quality,
+            ))
+            .with_context(|| format!("Failed to write {}", out_file.display()))?;
+        println!("{}", out_file.display());
+    }
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
 use std::sync::RwLock;
 
 use serde::{Deserialize, Serialize};
@@ -7,6 +8,30 @@ pub const CARD_IMAGE_URL_ROOT: &str = match option_env!("NRO_PROXY_CARD_IMAGE_UR
     Some(env) => env,
     None => "https://nro-public.s3.nl-ams.scw.cloud/nro/card-printings/v2/webp",
 };
+/// Comma-separated roots that mirror [`CARD_IMAGE_URL_ROOT`], tried in order
+/// when it cannot be reached.
+pub const CARD_IMAGE_URL_MIRRORS: &str = match option_env!("NRO_PROXY_CARD_IMAGE_URL_MIRRORS") {
+    Some(env) => env,
+    None => "",
+};
+
+/// The image root that has been seen working this session, which is tried
+/// before the others.
+static PREFERRED_IMAGE_ROOT: RwLock<Option<String>> = RwLock::new(None);
+
+#[allow(clippy::missing_panics_doc)]
+pub fn set_preferred_image_root(root: Option<String>) {
+    *PREFERRED_IMAGE_ROOT.write().expect("image root lock") = root;
+}
+
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn preferred_image_root() -> Option<String> {
+    PREFERRED_IMAGE_ROOT
+        .read()
+        .expect("image root lock")
+        .clone()
+}
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum CardImage {
@@ -31,16 +56,33 @@ pub struct CardFacePrintingId {
     pub print_group: String,
 }
 impl CardFacePrintingId {
+    /// The URL of the image in the default layout. Groups can host their
+    /// images elsewhere, so prefer [`MultiLibrary::card_image_url`].
     #[must_use]
     pub fn image_url(&self) -> String {
+        self.image_url_at(CARD_IMAGE_URL_ROOT)
+    }
+    /// The URL of the image in the default layout under an image root.
+    #[must_use]
+    pub fn image_url_at(&self, root: &str) -> String {
+        self.url_in(root, "card", "webp")
+    }
+    /// The URL of the small image shown in lists under an image root, which
+    /// the `thumbnails` tool writes as a JPEG to a `thumb` directory next to
+    /// the full size images.
+    #[must_use]
+    pub fn thumbnail_url_at(&self, root: &str) -> String {
+        self.url_in(root, "card/thumb", "jpg")
+    }
+    fn url_in(&self, root: &str, directory: &str, extension: &str) -> String {
         match self.face_or_variant_specifier {
             Some(face) => format!(
-                "{CARD_IMAGE_URL_ROOT}/{group}/card/{id:5>0}.{face}.webp",
+                "{root}/{group}/{directory}/{id:5>0}.{face}.{extension}",
                 group = self.print_group,
                 id = self.id
             ),
             None => format!(
-                "{CARD_IMAGE_URL_ROOT}/{group}/card/{id:5>0}.webp",
+                "{root}/{group}/{directory}/{id:5>0}.{extension}",
                 group = self.print_group,
                 id = self.id
             ),
@@ -56,8 +98,19 @@ pub struct InsertId {
 impl InsertId {
     #[must_use]
     pub fn image_url(&self) -> String {
+        self.image_url_at(CARD_IMAGE_URL_ROOT)
+    }
+    #[must_use]
+    pub fn image_url_at(&self, root: &str) -> String {
+        self.url_in(root, "insert", "webp")
+    }
+    #[must_use]
+    pub fn thumbnail_url_at(&self, root: &str) -> String {
+        self.url_in(root, "insert/thumb", "jpg")
+    }
+    fn url_in(&self, root: &str, directory: &str, extension: &str) -> String {
         format!(
-            "{CARD_IMAGE_URL_ROOT}/{group}/insert/{name}.webp",
+            "{root}/{group}/{directory}/{name}.{extension}",
             group = self.print_group,
             name = self.name
         )
@@ -67,6 +120,44 @@ impl InsertId {
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
 pub struct CardId(pub String);
 
+/// Where a print group's images are hosted, for groups that do not use the
+/// layout under [`CARD_IMAGE_URL_ROOT`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ImageUrlTemplate {
+    /// The URL of a card image. `{id}`, `{group}` and `{ext}` are replaced by
+    /// the printing id, print group and extension, and `{face}` by a dot and
+    /// the face or variant number for printings that have one.
+    pub card: String,
+    /// The URL of an insert image, with `{name}` replaced by the insert's
+    /// name. Inserts use the default layout when this is not set.
+    pub insert: Option<String>,
+    pub extension: String,
+}
+impl ImageUrlTemplate {
+    #[must_use]
+    pub fn card_url(&self, printing: &CardFacePrintingId) -> String {
+        let face = printing
+            .face_or_variant_specifier
+            .map(|face| format!(".{face}"))
+            .unwrap_or_default();
+        self.card
+            .replace("{id}", &printing.id.to_string())
+            .replace("{face}", &face)
+            .replace("{group}", &printing.print_group)
+            .replace("{ext}", &self.extension)
+    }
+    #[must_use]
+    pub fn insert_url(&self, insert: &InsertId) -> Option<String> {
+        let url = self
+            .insert
+            .as_ref()?
+            .replace("{name}", &insert.name)
+            .replace("{group}", &insert.print_group)
+            .replace("{ext}", &self.extension);
+        Some(url)
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct MultiLibrary {
     pub libraries: HashMap<String, Library>,
@@ -75,6 +166,48 @@ pub struct MultiLibrary {
     pub nrdb_remap: HashMap<u32, u32>,
     #[serde(default)]
     pub local_images: Vec<LocalImageOverride>,
+    #[serde(default)]
+    pub local_insert_images: Vec<LocalInsertImageOverride>,
+    /// Local card backs by side, such as `"corp"`, used for exports.
+    #[serde(default)]
+    pub local_back_images: HashMap<String, String>,
+    /// The print group whose art to use when a group is missing an image, such
+    /// as English for a fan translation.
+    #[serde(default)]
+    pub fallbacks: HashMap<String, String>,
+    #[serde(default)]
+    pub image_urls: HashMap<String, ImageUrlTemplate>,
+    /// Roots that mirror [`CARD_IMAGE_URL_ROOT`], after the ones set when
+    /// building.
+    #[serde(default)]
+    pub image_mirrors: Vec<String>,
+}
+
+/// Everything in a [`MultiLibrary`] apart from the libraries themselves. Each
+/// print group's library is kept in its own manifest so that the client only
+/// has to fetch the groups it uses.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ManifestIndex {
+    pub collection_names: HashMap<String, String>,
+    #[serde(default)]
+    pub nrdb_remap: HashMap<u32, u32>,
+    #[serde(default)]
+    pub local_images: Vec<LocalImageOverride>,
+    #[serde(default)]
+    pub local_insert_images: Vec<LocalInsertImageOverride>,
+    #[serde(default)]
+    pub local_back_images: HashMap<String, String>,
+    #[serde(default)]
+    pub fallbacks: HashMap<String, String>,
+    #[serde(default)]
+    pub image_urls: HashMap<String, ImageUrlTemplate>,
+    #[serde(default)]
+    pub image_mirrors: Vec<String>,
+    /// When the manifest last changed, in seconds since the Unix epoch. The
+    /// client uses whichever of its compiled-in and downloaded manifests has
+    /// the newer revision.
+    #[serde(default)]
+    pub revision: u64,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
@@ -85,7 +218,83 @@ pub struct LocalImageOverride {
     pub url: String,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct LocalInsertImageOverride {
+    pub insert: InsertId,
+    pub url: String,
+}
+
 impl MultiLibrary {
+    /// A library with none of its print groups loaded yet.
+    #[must_use]
+    pub fn from_index(index: ManifestIndex) -> MultiLibrary {
+        MultiLibrary {
+            libraries: HashMap::new(),
+            collection_names: index.collection_names,
+            nrdb_remap: index.nrdb_remap,
+            local_images: index.local_images,
+            local_insert_images: index.local_insert_images,
+            local_back_images: index.local_back_images,
+            fallbacks: index.fallbacks,
+            image_urls: index.image_urls,
+            image_mirrors: index.image_mirrors,
+        }
+    }
+    #[must_use]
+    pub fn index(&self, revision: u64) -> ManifestIndex {
+        ManifestIndex {
+            collection_names: self.collection_names.clone(),
+            nrdb_remap: self.nrdb_remap.clone(),
+            local_images: self.local_images.clone(),
+            local_insert_images: self.local_insert_images.clone(),
+            local_back_images: self.local_back_images.clone(),
+            fallbacks: self.fallbacks.clone(),
+            image_urls: self.image_urls.clone(),
+            image_mirrors: self.image_mirrors.clone(),
+            revision,
+        }
+    }
+    /// Adds a print group's library once it has been loaded. Anything a local
+    /// overlay already added to the group is kept.
+    pub fn insert_library(&mut self, group: String, mut library: Library) {
+        if let Some(overlay) = self.libraries.remove(&group) {
+            library.merge(&overlay);
+        }
+        self.libraries.insert(group, library);
+    }
+    /// The groups that need to be loaded to show cards from `group`, which is
+    /// the group itself followed by the groups it falls back to.
+    #[must_use]
+    pub fn group_with_fallbacks(&self, group: &str) -> Vec<String> {
+        let mut groups = vec![group.to_string()];
+        while let Some(fallback) = groups.last().and_then(|group| self.fallbacks.get(group))
+            && !groups.contains(fallback)
+        {
+            groups.push(fallback.clone());
+        }
+        groups
+    }
+    /// Lists every print group with its display name, sorted by name. Groups
+    /// without a name, such as ones only added by a local overlay, are named
+    /// after their id. Named groups are listed even before they are loaded.
+    #[must_use]
+    pub fn collections(&self) -> Vec<(String, String)> {
+        let mut collections = self
+            .libraries
+            .keys()
+            .chain(
+                self.collection_names
+                    .keys()
+                    .filter(|group| !self.libraries.contains_key(*group)),
+            )
+            .map(|group| {
+                let name = self.collection_names.get(group).unwrap_or(group);
+                (group.clone(), name.clone())
+            })
+            .collect::<Vec<_>>();
+        collections.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        collections
+    }
     #[must_use]
     pub fn local_image_url(&self, printing: &CardFacePrintingId) -> Option<&str> {
         self.local_images
@@ -97,6 +306,17 @@ impl MultiLibrary {
             })
             .map(|override_| override_.url.as_str())
     }
+    #[must_use]
+    pub fn local_insert_image_url(&self, insert: &InsertId) -> Option<&str> {
+        self.local_insert_images
+            .iter()
+            .find(|override_| override_.insert == *insert)
+            .map(|override_| override_.url.as_str())
+    }
+    #[must_use]
+    pub fn local_back_image_url(&self, side: &str) -> Option<&str> {
+        self.local_back_images.get(side).map(String::as_str)
+    }
 
     pub fn merge_overlay(&mut self, overlay: MultiLibrary) {
         for (group, library) in overlay.libraries {
@@ -114,10 +334,191 @@ impl MultiLibrary {
             self.nrdb_remap.insert(from, to);
         }
         self.local_images.extend(overlay.local_images);
+        self.local_insert_images.extend(overlay.local_insert_images);
+        for (side, url) in overlay.local_back_images {
+            self.local_back_images.insert(side, url);
+        }
+        for (group, fallback) in overlay.fallbacks {
+            self.fallbacks.insert(group, fallback);
+        }
+        for (group, template) in overlay.image_urls {
+            self.image_urls.insert(group, template);
+        }
+        for mirror in overlay.image_mirrors {
+            if !self.image_mirrors.contains(&mirror) {
+                self.image_mirrors.push(mirror);
+            }
+        }
+    }
+    /// Every root the default image layout is hosted under, with the one
+    /// that has been working this session first.
+    #[must_use]
+    pub fn image_roots(&self) -> Vec<String> {
+        let mut roots = vec![CARD_IMAGE_URL_ROOT.to_string()];
+        let mirrors = CARD_IMAGE_URL_MIRRORS
+            .split(',')
+            .map(str::trim)
+            .chain(self.image_mirrors.iter().map(String::as_str));
+        for mirror in mirrors {
+            let mirror = mirror.trim_end_matches('/');
+            if !mirror.is_empty() && !roots.iter().any(|root| root == mirror) {
+                roots.push(mirror.to_string());
+            }
+        }
+        if let Some(preferred) = preferred_image_root()
+            && let Some(index) = roots.iter().position(|root| *root == preferred)
+        {
+            let preferred = roots.remove(index);
+            roots.insert(0, preferred);
+        }
+        roots
+    }
+    /// The image root `url` is under, if it is one of
+    /// [`MultiLibrary::image_roots`].
+    #[must_use]
+    pub fn image_root_of(&self, url: &str) -> Option<String> {
+        self.image_roots()
+            .into_iter()
+            .find(|root| url.starts_with(&format!("{root}/")))
+    }
+    /// Every URL a card image can be loaded from, trying each image root in
+    /// turn for groups that use the default layout.
+    #[must_use]
+    pub fn card_image_urls(&self, printing: &CardFacePrintingId) -> Vec<String> {
+        match self.image_urls.get(&printing.print_group) {
+            Some(template) => vec![template.card_url(printing)],
+            None => self
+                .image_roots()
+                .iter()
+                .map(|root| printing.image_url_at(root))
+                .collect(),
+        }
+    }
+    /// Every URL a card's thumbnail can be loaded from. Groups with their own
+    /// image template have no thumbnails.
+    #[must_use]
+    pub fn card_thumbnail_urls(&self, printing: &CardFacePrintingId) -> Vec<String> {
+        if self.image_urls.contains_key(&printing.print_group) {
+            return Vec::new();
+        }
+        self.image_roots()
+            .iter()
+            .map(|root| printing.thumbnail_url_at(root))
+            .collect()
+    }
+    #[must_use]
+    pub fn insert_thumbnail_urls(&self, insert: &InsertId) -> Vec<String> {
+        if self
+            .image_urls
+            .get(&insert.print_group)
+            .is_some_and(|template| template.insert.is_some())
+        {
+            return Vec::new();
+        }
+        self.image_roots()
+            .iter()
+            .map(|root| insert.thumbnail_url_at(root))
+            .collect()
+    }
+    #[must_use]
+    pub fn insert_image_urls(&self, insert: &InsertId) -> Vec<String> {
+        match self
+            .image_urls
+            .get(&insert.print_group)
+            .and_then(|template| template.insert_url(insert))
+        {
+            Some(url) => vec![url],
+            None => self
+                .image_roots()
+                .iter()
+                .map(|root| insert.image_url_at(root))
+                .collect(),
+        }
+    }
+    /// The URL of a card image, using the print group's template if it has
+    /// one.
+    #[must_use]
+    pub fn card_image_url(&self, printing: &CardFacePrintingId) -> String {
+        self.image_urls.get(&printing.print_group).map_or_else(
+            || printing.image_url(),
+            |template| template.card_url(printing),
+        )
+    }
+    #[must_use]
+    pub fn insert_image_url(&self, insert: &InsertId) -> String {
+        self.image_urls
+            .get(&insert.print_group)
+            .and_then(|template| template.insert_url(insert))
+            .unwrap_or_else(|| insert.image_url())
+    }
+    /// The same face of a card in the group `printing`'s group falls back to.
+    #[must_use]
+    pub fn fallback_printing(&self, printing: &CardFacePrintingId) -> Option<CardFacePrintingId> {
+        self.equivalent_printing(printing, self.fallbacks.get(&printing.print_group)?)
+    }
+    /// The same face of a card in another group. Uses the same printing if
+    /// that group has it, or else the newest printing of the card there.
+    #[must_use]
+    pub fn equivalent_printing(
+        &self,
+        printing: &CardFacePrintingId,
+        group: &str,
+    ) -> Option<CardFacePrintingId> {
+        let library = self.libraries.get(group)?;
+        let same = CardFacePrintingId {
+            print_group: group.to_string(),
+            ..printing.clone()
+        };
+        if library.faces.contains_key(&same) {
+            return Some(same);
+        }
+        let card_id = &self
+            .libraries
+            .get(&printing.print_group)?
+            .faces
+            .get(printing)?
+            .card_id;
+        let card = library.try_get_card(card_id)?;
+        card.printings
+            .iter()
+            .rev()
+            .find(|face| face.face_or_variant_specifier == printing.face_or_variant_specifier)
+            .or_else(|| card.printings.last())
+            .cloned()
+    }
+    /// Every group that has the card `printing` belongs to, with its display
+    /// name and the equivalent face there, in the order of
+    /// [`MultiLibrary::collections`].
+    #[must_use]
+    pub fn group_alternatives(
+        &self,
+        printing: &CardFacePrintingId,
+    ) -> Vec<(String, String, CardFacePrintingId)> {
+        self.collections()
+            .into_iter()
+            .filter_map(|(group, name)| {
+                let face = self.equivalent_printing(printing, &group)?;
+                Some((group, name, face))
+            })
+            .collect()
+    }
+    /// The same insert in the group `insert`'s group falls back to.
+    #[must_use]
+    pub fn fallback_insert(&self, insert: &InsertId) -> Option<InsertId> {
+        let fallback = self.fallbacks.get(&insert.print_group)?;
+        let same = InsertId {
+            name: insert.name.clone(),
+            print_group: fallback.clone(),
+        };
+        self.libraries
+            .get(fallback)?
+            .inserts
+            .contains_key(&same)
+            .then_some(same)
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Library {
     pub cards: HashMap<CardId, CardMetadata>,
     pub faces: HashMap<CardFacePrintingId, PrintingMetadata>,
@@ -142,6 +543,51 @@ impl Library {
         let card_id = self.faces.get(id).map(|printing| &printing.card_id)?;
         self.cards.get(card_id)
     }
+    /// Lists the names of every printing in the library, oldest first.
+    #[must_use]
+    pub fn printing_names(&self) -> Vec<String> {
+        let mut first_printing: HashMap<&str, u32> = HashMap::new();
+        for printing in self.faces.values() {
+            let first = first_printing
+                .entry(&printing.printing_name)
+                .or_insert(printing.id.id);
+            *first = (*first).min(printing.id.id);
+        }
+        let mut names = first_printing.into_iter().collect::<Vec<_>>();
+        names.sort_by_key(|&(name, first)| (first, name));
+        names
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+    /// Lists each card printed in the set named `printing_name`, in printing
+    /// order, described by the first face of its printing.
+    #[must_use]
+    pub fn set_contents(&self, printing_name: &str) -> Vec<&PrintingMetadata> {
+        let mut contents = self
+            .faces
+            .values()
+            .filter(|printing| printing.printing_name == printing_name)
+            .collect::<Vec<_>>();
+        contents.sort_by(|a, b| a.id.cmp(&b.id));
+        contents.dedup_by_key(|printing| printing.id.id);
+        contents
+    }
+    /// Lists each printing of a card once, oldest first, described by the
+    /// first face of the printing.
+    #[must_use]
+    pub fn card_printings(&self, card: &CardMetadata) -> Vec<&PrintingMetadata> {
+        let mut printings: Vec<&PrintingMetadata> = Vec::new();
+        for face in &card.printings {
+            if printings.last().is_some_and(|p| p.id.id == face.id) {
+                continue;
+            }
+            if let Some(printing) = self.faces.get(face) {
+                printings.push(printing);
+            }
+        }
+        printings
+    }
     #[must_use]
     pub fn get_insert(&self, id: &InsertId) -> &InsertMetadata {
         &self.inserts[id]
@@ -155,6 +601,7 @@ impl Library {
                     alternate_face_data: meta.alternate_face_data.clone(),
                     id: meta.id.clone(),
                     printings: BTreeSet::new(),
+                    details: meta.details.clone(),
                 })
                 .printings
                 .extend(meta.printings.iter().cloned());
@@ -177,6 +624,13 @@ pub struct PrintingMetadata {
     pub id: CardFacePrintingId,
     pub card_id: CardId,
     pub printing_name: String,
+    /// How many copies of the card come in the printing's product, as
+    /// supplied by its printing data in NRDB.
+    #[serde(default)]
+    pub quantity: Option<u32>,
+    /// The NRDB code of the pack or set the printing belongs to.
+    #[serde(default)]
+    pub pack: Option<String>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -197,6 +651,167 @@ pub struct CardMetadata {
     pub id: CardId,
     /// The ID of the cards printings in NRDB
     pub printings: BTreeSet<CardFacePrintingId>,
+    /// Gameplay details used to filter searches
+    #[serde(default)]
+    pub details: CardDetails,
+}
+
+/// The gameplay details of a card, as supplied by its card data in NRDB. All
+/// ids are NRDB codes, such as `anarch`, `runner` or `ice`.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct CardDetails {
+    pub faction: Option<String>,
+    pub side: Option<String>,
+    pub card_type: Option<String>,
+    pub subtypes: Vec<String>,
+    /// The rules text, reduced to ASCII characters.
+    pub text: Option<String>,
+}
+
+impl CardMetadata {
+    /// Finds a face of one of the card's printings, falling back to the first
+    /// face of that printing if it lacks the requested one.
+    #[must_use]
+    pub fn printing_face(&self, printing: u32, face: Option<usize>) -> Option<&CardFacePrintingId> {
+        let mut faces = self.printings.iter().filter(|f| f.id == printing);
+        faces
+            .clone()
+            .find(|f| f.face_or_variant_specifier == face)
+            .or_else(|| faces.next())
+    }
+}
+
+/// A single `key:value` term of a [`SearchQuery`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum SearchFilter {
+    /// `f:`, a prefix of the faction code.
+    Faction(String),
+    /// `d:`, a prefix of the side code.
+    Side(String),
+    /// `t:`, a prefix of the type or any subtype code.
+    Type(String),
+    /// `s:`, part of the set name or a prefix of the pack code of any printing.
+    Set(String),
+    /// `x:`, part of the rules text.
+    Text(String),
+}
+impl SearchFilter {
+    fn parse(term: &str) -> Option<SearchFilter> {
+        let (key, value) = term.split_once(':')?;
+        let value = value.to_lowercase();
+        match key.to_lowercase().as_str() {
+            "f" => Some(SearchFilter::Faction(value)),
+            "d" => Some(SearchFilter::Side(value)),
+            "t" => Some(SearchFilter::Type(value)),
+            "s" => Some(SearchFilter::Set(value)),
+            "x" => Some(SearchFilter::Text(value)),
+            _ => None,
+        }
+    }
+    #[must_use]
+    pub fn matches(&self, library: &Library, card: &CardMetadata) -> bool {
+        // NRDB codes use dashes where names have spaces, such as `haas-bioroid`.
+        let code_matches = |code: Option<&str>, value: &str| {
+            code.is_some_and(|code| code.starts_with(&value.replace(' ', "-")))
+        };
+        let details = &card.details;
+        match self {
+            SearchFilter::Faction(value) => code_matches(details.faction.as_deref(), value),
+            SearchFilter::Side(value) => code_matches(details.side.as_deref(), value),
+            SearchFilter::Type(value) => {
+                code_matches(details.card_type.as_deref(), value)
+                    || details
+                        .subtypes
+                        .iter()
+                        .any(|subtype| code_matches(Some(subtype), value))
+            }
+            SearchFilter::Set(value) => card
+                .printings
+                .iter()
+                .filter_map(|face| library.faces.get(face))
+                .any(|printing| {
+                    printing.printing_name.to_lowercase().contains(value)
+                        || code_matches(printing.pack.as_deref(), value)
+                }),
+            SearchFilter::Text(value) => details
+                .text
+                .as_ref()
+                .is_some_and(|text| text.to_lowercase().contains(value)),
+        }
+    }
+}
+
+/// The copy counts a search can ask for.
+const MAX_SEARCH_COPIES: std::ops::RangeInclusive<usize> = 1..=99;
+
+/// A search in NRDB-like syntax, such as `f:anarch t:ice s:elevation x:"gain 2"`.
+/// Terms with a known key become filters; everything else is matched fuzzily
+/// against titles. Values containing spaces can be wrapped in double quotes,
+/// and a leading number asks for that many copies.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct SearchQuery {
+    pub title: String,
+    pub filters: Vec<SearchFilter>,
+    /// A leading count such as the `3` in `3 Hedge Fund` or `3x Hedge Fund`.
+    pub copies: Option<usize>,
+}
+impl SearchQuery {
+    #[must_use]
+    pub fn parse(input: &str) -> SearchQuery {
+        let mut terms = Vec::new();
+        let mut term = String::new();
+        let mut quoted = false;
+        for c in input.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                c if c.is_whitespace() && !quoted => {
+                    if !term.is_empty() {
+                        terms.push(std::mem::take(&mut term));
+                    }
+                }
+                c => term.push(c),
+            }
+        }
+        if !term.is_empty() {
+            terms.push(term);
+        }
+        let mut query = SearchQuery::default();
+        if terms.len() > 1 {
+            let count = terms[0].strip_suffix(['x', 'X']).unwrap_or(&terms[0]);
+            // Anything else, such as `0` or a year, is part of the title.
+            if let Ok(copies) = count.parse()
+                && MAX_SEARCH_COPIES.contains(&copies)
+            {
+                query.copies = Some(copies);
+                terms.remove(0);
+            }
+        }
+        let mut title = Vec::new();
+        for term in terms {
+            match SearchFilter::parse(&term) {
+                Some(filter) => query.filters.push(filter),
+                None => title.push(term),
+            }
+        }
+        query.title = title.join(" ");
+        query
+    }
+    /// How many copies to add of the chosen result.
+    #[must_use]
+    pub fn copies(&self) -> usize {
+        self.copies.unwrap_or(1)
+    }
+    #[must_use]
+    pub fn is_filtered(&self) -> bool {
+        !self.filters.is_empty()
+    }
+    /// Whether a card passes every filter. The title is not checked.
+    #[must_use]
+    pub fn matches(&self, library: &Library, card: &CardMetadata) -> bool {
+        self.filters
+            .iter()
+            .all(|filter| filter.matches(library, card))
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
@@ -226,26 +841,93 @@ pub enum FilledCardSlot {
 impl FilledCardSlot {
     #[must_use]
     pub fn is_local_override(&self) -> bool {
+        let library = ACTIVE_LIBRARY.read().expect("library lock");
         match self {
-            FilledCardSlot::Card { printing } => ACTIVE_LIBRARY
-                .read()
-                .expect("library lock")
-                .local_image_url(printing)
-                .is_some(),
-            FilledCardSlot::Insert { .. } => false,
+            FilledCardSlot::Card { printing } => library.local_image_url(printing).is_some(),
+            FilledCardSlot::Insert { insert } => library.local_insert_image_url(insert).is_some(),
         }
     }
 
     #[must_use]
     pub fn image_url(&self) -> String {
+        let library = ACTIVE_LIBRARY.read().expect("library lock");
         match self {
-            FilledCardSlot::Card { printing } => ACTIVE_LIBRARY
-                .read()
-                .expect("library lock")
+            FilledCardSlot::Card { printing } => library
                 .local_image_url(printing)
-                .map(str::to_string)
-                .unwrap_or_else(|| printing.image_url()),
-            FilledCardSlot::Insert { insert } => insert.image_url(),
+                .map_or_else(|| library.card_image_url(printing), str::to_string),
+            FilledCardSlot::Insert { insert } => library
+                .local_insert_image_url(insert)
+                .map_or_else(|| library.insert_image_url(insert), str::to_string),
+        }
+    }
+    /// Every URL the slot's image can be loaded from, best first: a local
+    /// override, then the image itself from each mirror, then the images of
+    /// the groups it falls back to.
+    #[must_use]
+    pub fn image_urls(&self) -> Vec<String> {
+        self.urls(false)
+    }
+    /// Like [`FilledCardSlot::image_urls`], but trying the thumbnail on each
+    /// mirror before the full size image there, for showing the slot in a list.
+    #[must_use]
+    pub fn thumbnail_urls(&self) -> Vec<String> {
+        self.urls(true)
+    }
+    fn urls(&self, thumbnails: bool) -> Vec<String> {
+        let library = ACTIVE_LIBRARY.read().expect("library lock");
+        let mut urls = Vec::new();
+        let mut visited = HashSet::new();
+        match self {
+            FilledCardSlot::Card { printing } => {
+                if let Some(url) = library.local_image_url(printing) {
+                    urls.push(url.to_string());
+                }
+                let mut printing = Some(printing.clone());
+                while let Some(current) = printing
+                    && visited.insert(current.print_group.clone())
+                {
+                    let thumbnail_urls = if thumbnails {
+                        library.card_thumbnail_urls(&current)
+                    } else {
+                        Vec::new()
+                    };
+                    extend_interleaved(
+                        &mut urls,
+                        thumbnail_urls,
+                        library.card_image_urls(&current),
+                    );
+                    printing = library.fallback_printing(&current);
+                }
+            }
+            FilledCardSlot::Insert { insert } => {
+                if let Some(url) = library.local_insert_image_url(insert) {
+                    urls.push(url.to_string());
+                }
+                let mut insert = Some(insert.clone());
+                while let Some(current) = insert
+                    && visited.insert(current.print_group.clone())
+                {
+                    let thumbnail_urls = if thumbnails {
+                        library.insert_thumbnail_urls(&current)
+                    } else {
+                        Vec::new()
+                    };
+                    extend_interleaved(
+                        &mut urls,
+                        thumbnail_urls,
+                        library.insert_image_urls(&current),
+                    );
+                    insert = library.fallback_insert(&current);
+                }
+            }
+        }
+        urls
+    }
+    #[must_use]
+    pub fn print_group(&self) -> &str {
+        match self {
+            FilledCardSlot::Card { printing } => &printing.print_group,
+            FilledCardSlot::Insert { insert } => &insert.print_group,
         }
     }
     #[must_use]
@@ -283,6 +965,73 @@ impl FilledCardSlot {
                 ),
         }
     }
+    /// A label for which face or variant of the card this slot uses, such as
+    /// "Face 2" or "Variant 3". Single-faced cards and inserts have none.
+    #[must_use]
+    pub fn face_name(&self) -> Option<String> {
+        let FilledCardSlot::Card { printing } = self else {
+            return None;
+        };
+        let face = printing.face_or_variant_specifier?;
+        let library = ACTIVE_LIBRARY.read().expect("library lock");
+        let card = library
+            .libraries
+            .get(&printing.print_group)
+            .and_then(|group| group.try_get_face_card(printing))?;
+        match card.alternate_face_data {
+            AlternateFaceMetadata::Single => None,
+            AlternateFaceMetadata::Multiple(_) => Some(format!("Face {face}")),
+            AlternateFaceMetadata::Variants(_) => Some(format!("Variant {face}")),
+        }
+    }
+    /// The name of the printing this slot uses, such as "Elevation (English)".
+    /// Inserts have no printing, so this is always `None` for them.
+    #[must_use]
+    pub fn printing_name(&self) -> Option<String> {
+        match self {
+            FilledCardSlot::Card { printing } => ACTIVE_LIBRARY
+                .read()
+                .expect("library lock")
+                .libraries
+                .get(&printing.print_group)
+                .and_then(|group| group.faces.get(printing))
+                .map(|printing| printing.printing_name.clone()),
+            FilledCardSlot::Insert { .. } => None,
+        }
+    }
+}
+
+/// Adds each image URL after the thumbnail on the same image root, so that a
+/// missing thumbnail falls back to the full size image before another mirror.
+fn extend_interleaved(
+    urls: &mut Vec<String>,
+    thumbnail_urls: Vec<String>,
+    image_urls: Vec<String>,
+) {
+    let mut thumbnail_urls = thumbnail_urls.into_iter();
+    for image_url in image_urls {
+        urls.extend(thumbnail_urls.next());
+        urls.push(image_url);
+    }
+}
+
+/// The card and variant a printing counts towards when variants are handed
+/// out in rotation, if the printing belongs to a card with variants.
+fn variant_key(printing: &CardFacePrintingId) -> Option<(CardId, usize)> {
+    let variant = printing.face_or_variant_specifier?;
+    let library = ACTIVE_LIBRARY.read().expect("library lock");
+    match library
+        .libraries
+        .get(&printing.print_group)
+        .and_then(|library| library.try_get_face_card(printing))
+    {
+        Some(CardMetadata {
+            alternate_face_data: AlternateFaceMetadata::Variants(_),
+            id,
+            ..
+        }) => Some((id.clone(), variant)),
+        _ => None,
+    }
 }
 
 #[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -291,6 +1040,33 @@ pub struct PrintFile {
     auto_faces: HashMap<(CardId, usize), usize>,
 }
 impl PrintFile {
+    /// Builds a print file from a list of slots, recomputing which variants
+    /// have been handed out so that further additions keep rotating them.
+    #[must_use]
+    pub fn from_slots(slots: Vec<FilledCardSlot>) -> PrintFile {
+        let mut print_file = PrintFile {
+            slots,
+            auto_faces: HashMap::new(),
+        };
+        print_file.recount_variants();
+        print_file
+    }
+    /// Recomputes which variants have been handed out. Variants can only be
+    /// counted once their print group is loaded, so this is redone whenever
+    /// one is. Returns whether the counts changed.
+    pub fn recount_variants(&mut self) -> bool {
+        let mut auto_faces: HashMap<(CardId, usize), usize> = HashMap::new();
+        for slot in &self.slots {
+            if let FilledCardSlot::Card { printing } = slot
+                && let Some(key) = variant_key(printing)
+            {
+                *auto_faces.entry(key).or_default() += 1;
+            }
+        }
+        let changed = auto_faces != self.auto_faces;
+        self.auto_faces = auto_faces;
+        changed
+    }
     pub fn clear(&mut self) {
         self.slots.clear();
         self.auto_faces.clear();
@@ -311,92 +1087,118 @@ impl PrintFile {
     pub fn get(&self, index: usize) -> Option<&FilledCardSlot> {
         self.slots.get(index)
     }
+    /// Moves the slot at `from` to `to`, shifting the slots in between.
+    pub fn move_slot(&mut self, from: usize, to: usize) {
+        if from < self.slots.len() && to < self.slots.len() {
+            let slot = self.slots.remove(from);
+            self.slots.insert(to, slot);
+        }
+    }
+    pub fn swap_slots(&mut self, a: usize, b: usize) {
+        if a < self.slots.len() && b < self.slots.len() {
+            self.slots.swap(a, b);
+        }
+    }
     pub fn add_insert(&mut self, insert: InsertId) {
         self.slots.push(FilledCardSlot::Insert { insert });
     }
     pub fn remove_card(&mut self, index: usize) {
         if index < self.slots.len() {
             let slot = self.slots.remove(index);
-            if let &FilledCardSlot::Card {
-                printing:
-                    ref face @ CardFacePrintingId {
-                        face_or_variant_specifier: Some(variant),
-                        ref print_group,
-                        ..
-                    },
-                } = &slot
-            {
-                if let Some(CardMetadata {
-                    alternate_face_data: AlternateFaceMetadata::Variants(_),
-                    id,
-                    ..
-                }) = ACTIVE_LIBRARY
-                    .read()
-                    .expect("library lock")
-                    .libraries
-                    .get(print_group)
-                    .and_then(|library| library.try_get_face_card(face))
-                {
-                    let auto_faces = self.auto_faces.entry((id.clone(), variant)).or_default();
-                    *auto_faces = auto_faces.saturating_sub(1);
-                }
+            if let FilledCardSlot::Card { printing } = &slot {
+                self.release_variant(printing);
             }
         }
     }
     pub fn update_card(&mut self, index: usize, card: CardFacePrintingId) {
+        if let Some(FilledCardSlot::Card { printing }) = self.slots.get(index).cloned() {
+            self.release_variant(&printing);
+        }
         if let Some(slot) = self.slots.get_mut(index) {
-            if let &FilledCardSlot::Card {
-                printing:
-                    ref face @ CardFacePrintingId {
-                        face_or_variant_specifier: Some(variant),
-                        ..
-                    },
-            } = &*slot
-            {
-                if let Some(CardMetadata {
-                    alternate_face_data: AlternateFaceMetadata::Variants(_),
-                    id,
-                    ..
-                }) = ACTIVE_LIBRARY
-                    .read()
-                    .expect("library lock")
-                    .libraries
-                    .get(&card.print_group)
-                    .and_then(|library| library.try_get_face_card(face))
-                {
-                    let auto_faces = self.auto_faces.entry((id.clone(), variant)).or_default();
-                    *auto_faces = auto_faces.saturating_sub(1);
-                }
+            if let Some(key) = variant_key(&card) {
+                *self.auto_faces.entry(key).or_default() += 1;
             }
             *slot = FilledCardSlot::Card { printing: card };
         }
     }
+    fn release_variant(&mut self, printing: &CardFacePrintingId) {
+        if let Some(key) = variant_key(printing) {
+            let auto_faces = self.auto_faces.entry(key).or_default();
+            *auto_faces = auto_faces.saturating_sub(1);
+        }
+    }
+    /// Switches every card that was printed in the set named `printing_name`
+    /// to that printing, keeping the face or variant of each slot. Returns how
+    /// many slots changed.
+    pub fn switch_printing(&mut self, printing_name: &str) -> usize {
+        let replacements = {
+            let library = ACTIVE_LIBRARY.read().expect("library lock");
+            self.slots
+                .iter()
+                .enumerate()
+                .filter_map(|(index, slot)| {
+                    let FilledCardSlot::Card { printing } = slot else {
+                        return None;
+                    };
+                    let group = library.libraries.get(&printing.print_group)?;
+                    let card = group.try_get_face_card(printing)?;
+                    let target = group
+                        .card_printings(card)
+                        .into_iter()
+                        .rev()
+                        .find(|meta| meta.printing_name == printing_name)?;
+                    let face =
+                        card.printing_face(target.id.id, printing.face_or_variant_specifier)?;
+                    (face != printing).then(|| (index, face.clone()))
+                })
+                .collect::<Vec<_>>()
+        };
+        let changed = replacements.len();
+        for (index, face) in replacements {
+            self.update_card(index, face);
+        }
+        changed
+    }
+    /// Adds a copy of a card using the printing picked by `preference`.
+    pub fn add_cards(&mut self, meta: &CardMetadata, preference: &PrintingPreference) {
+        let printing = preference.choose(&ACTIVE_LIBRARY.read().expect("library lock"), meta);
+        self.add_card_printing(meta, printing);
+    }
+    /// Adds a copy of a card using the given printing id. Faces the printing
+    /// lacks fall back to the newest printing that has them.
     #[allow(clippy::missing_panics_doc)]
-    pub fn add_cards(&mut self, meta: &CardMetadata) {
+    pub fn add_card_printing(&mut self, meta: &CardMetadata, printing: u32) {
+        let find_face = |face: usize| {
+            let mut faces = meta
+                .printings
+                .iter()
+                .rev()
+                .filter(|f| f.face_or_variant_specifier == Some(face));
+            faces
+                .clone()
+                .find(|f| f.id == printing)
+                .or_else(|| faces.next())
+                .cloned()
+                .unwrap()
+        };
         match &meta.alternate_face_data {
             AlternateFaceMetadata::Single => self.slots.push(FilledCardSlot::Card {
-                printing: meta.printings.last().cloned().expect("No printings"),
-            }),
-            AlternateFaceMetadata::Multiple(titles) => {
-                let face = meta
+                printing: meta
                     .printings
                     .iter()
                     .rev()
-                    .find(|f| f.face_or_variant_specifier == Some(1))
-                    .unwrap();
+                    .find(|f| f.id == printing)
+                    .or_else(|| meta.printings.last())
+                    .cloned()
+                    .expect("No printings"),
+            }),
+            AlternateFaceMetadata::Multiple(titles) => {
                 self.slots.push(FilledCardSlot::Card {
-                    printing: face.clone(),
+                    printing: find_face(1),
                 });
-
                 for (face, _) in titles.iter().enumerate() {
-                    let face = meta
-                        .printings
-                        .iter()
-                        .rev()
-                        .find(|f| f.face_or_variant_specifier == Some(face + 2))
-                        .unwrap();
                     self.slots.push(FilledCardSlot::Card {
-                        printing: face.clone(),
+                        printing: find_face(face + 2),
                     });
                 }
             }
@@ -418,29 +1220,765 @@ impl PrintFile {
                     .auto_faces
                     .entry((meta.id.clone(), next_variant))
                     .or_default() += 1;
-                let face = meta
-                    .printings
-                    .iter()
-                    .rev()
-                    .find(|f| f.face_or_variant_specifier == Some(next_variant))
-                    .unwrap();
                 self.slots.push(FilledCardSlot::Card {
-                    printing: face.clone(),
+                    printing: find_face(next_variant),
                 });
             }
         }
     }
+    /// Removes the last copy of a printing, along with the extra faces that
+    /// were printed for it if it is a flip card. Does nothing if the file has
+    /// no copy of the printing.
+    pub fn remove_copy(&mut self, printing: u32, print_group: &str) {
+        let (index, extra_faces) = {
+            let library = ACTIVE_LIBRARY.read().expect("library lock");
+            let Some(group) = library.libraries.get(print_group) else {
+                return;
+            };
+            let is_copy = |slot: &FilledCardSlot| match slot {
+                FilledCardSlot::Card { printing: face } => {
+                    face.id == printing
+                        && face.print_group == print_group
+                        && match group.try_get_face_card(face) {
+                            Some(CardMetadata {
+                                alternate_face_data: AlternateFaceMetadata::Multiple(_),
+                                ..
+                            }) => face.face_or_variant_specifier == Some(1),
+                            _ => true,
+                        }
+                }
+                FilledCardSlot::Insert { .. } => false,
+            };
+            let Some(index) = self.slots.iter().rposition(is_copy) else {
+                return;
+            };
+            // The extra faces follow their front face, but may come from a
+            // fallback printing when the removed one lacks them.
+            let extra_faces = match &self.slots[index] {
+                FilledCardSlot::Card { printing } => match group.try_get_face_card(printing) {
+                    Some(CardMetadata {
+                        alternate_face_data: AlternateFaceMetadata::Multiple(titles),
+                        id: card_id,
+                        ..
+                    }) => self.slots[index + 1..]
+                        .iter()
+                        .zip(2..titles.len() + 2)
+                        .take_while(|(slot, face)| match slot {
+                            FilledCardSlot::Card { printing: extra } => {
+                                extra.face_or_variant_specifier == Some(*face)
+                                    && library
+                                        .libraries
+                                        .get(&extra.print_group)
+                                        .and_then(|group| group.try_get_face_card(extra))
+                                        .is_some_and(|card| card.id == *card_id)
+                            }
+                            FilledCardSlot::Insert { .. } => false,
+                        })
+                        .count(),
+                    _ => 0,
+                },
+                FilledCardSlot::Insert { .. } => 0,
+            };
+            (index, extra_faces)
+        };
+        for _ in 0..=extra_faces {
+            self.remove_card(index);
+        }
+    }
+
+    /// Groups the card slots into one entry per printing, counting physical
+    /// copies. Inserts are skipped, and the extra faces of flip cards are not
+    /// counted again since they are printed alongside their front face.
+    #[must_use]
+    pub fn decklist(&self) -> Vec<DecklistEntry> {
+        let library = ACTIVE_LIBRARY.read().expect("library lock");
+        let mut entries: Vec<DecklistEntry> = Vec::new();
+        for slot in &self.slots {
+            let FilledCardSlot::Card { printing } = slot else {
+                continue;
+            };
+            let Some(group) = library.libraries.get(&printing.print_group) else {
+                continue;
+            };
+            let Some(printing_meta) = group.faces.get(printing) else {
+                continue;
+            };
+            let Some(card) = group.try_get_card(&printing_meta.card_id) else {
+                continue;
+            };
+            if matches!(card.alternate_face_data, AlternateFaceMetadata::Multiple(_))
+                && printing
+                    .face_or_variant_specifier
+                    .is_some_and(|face| face > 1)
+            {
+                continue;
+            }
+            match entries.iter_mut().find(|entry| {
+                entry.printing_id == printing.id && entry.print_group == printing.print_group
+            }) {
+                Some(entry) => entry.count += 1,
+                None => entries.push(DecklistEntry {
+                    card_id: card.id.clone(),
+                    title: card.title.clone(),
+                    printing_id: printing.id,
+                    print_group: printing.print_group.clone(),
+                    printing_name: printing_meta.printing_name.clone(),
+                    count: 1,
+                }),
+            }
+        }
+        entries
+    }
+
+    /// Lists every slot along with its page, row and column in the generated PDF.
+    #[must_use]
+    pub fn pick_list(&self) -> Vec<PickListEntry> {
+        self.slots
+            .iter()
+            .enumerate()
+            .map(|(i, slot)| PickListEntry {
+                position: SlotPosition::of(i),
+                title: slot.name(),
+                printing_name: slot.printing_name(),
+                face: slot.face_name(),
+            })
+            .collect()
+    }
+
+    /// Serializes [`PrintFile::pick_list`] as CSV, with one-based positions.
+    #[must_use]
+    pub fn to_pick_list_csv(&self) -> String {
+        fn escape(field: &str) -> String {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        }
+        let mut out = String::from("Page,Row,Column,Title,Printing,Face\n");
+        for entry in self.pick_list() {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                entry.position.page + 1,
+                entry.position.row + 1,
+                entry.position.column + 1,
+                escape(&entry.title),
+                escape(entry.printing_name.as_deref().unwrap_or_default()),
+                escape(entry.face.as_deref().unwrap_or_default()),
+            );
+        }
+        out
+    }
+
+    /// Serializes the file as `N Card Title` lines, as accepted by Jinteki.net.
+    #[must_use]
+    pub fn to_jnet_text(&self) -> String {
+        let mut counts: Vec<(Title, usize)> = Vec::new();
+        for entry in self.decklist() {
+            match counts.iter_mut().find(|(title, _)| *title == entry.title) {
+                Some((_, count)) => *count += entry.count,
+                None => counts.push((entry.title, entry.count)),
+            }
+        }
+        let mut out = String::new();
+        for (title, count) in counts {
+            let _ = writeln!(out, "{count} {}", title.title);
+        }
+        out
+    }
+
+    /// Serializes the file as an NRDB `cards` object, mapping printing IDs to
+    /// counts. Remapped printings keep the ID they were remapped to, since
+    /// several NRDB IDs can share one.
+    #[must_use]
+    pub fn to_nrdb_json(&self) -> String {
+        let mut cards = BTreeMap::new();
+        for entry in self.decklist() {
+            *cards
+                .entry(format!("{:0>5}", entry.printing_id))
+                .or_insert(0) += entry.count;
+        }
+        serde_json::to_string_pretty(&cards).expect("Failed to serialize decklist")
+    }
+
+    /// Serializes the file as a checklist grouped by printing name, useful to
+    /// tick off cards while sorting printed sheets.
+    #[must_use]
+    pub fn to_checklist(&self) -> String {
+        let mut groups: BTreeMap<String, Vec<DecklistEntry>> = BTreeMap::new();
+        for entry in self.decklist() {
+            groups
+                .entry(entry.printing_name.clone())
+                .or_default()
+                .push(entry);
+        }
+        let mut out = String::new();
+        for (printing_name, mut entries) in groups {
+            entries.sort_by(|a, b| a.title.title.cmp(&b.title.title));
+            let total = entries.iter().map(|entry| entry.count).sum::<usize>();
+            let _ = writeln!(out, "{printing_name} ({total})");
+            for entry in entries {
+                let _ = writeln!(out, "[ ] {}x {}", entry.count, entry.title.title);
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// A single slot of a [`PrintFile`] and where it lands on the printed sheets.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PickListEntry {
+    pub position: SlotPosition,
+    pub title: String,
+    pub printing_name: Option<String>,
+    pub face: Option<String>,
+}
+
+/// A single printing of a card in a [`PrintFile`] and how many copies of it
+/// the file contains.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DecklistEntry {
+    pub card_id: CardId,
+    pub title: Title,
+    pub printing_id: u32,
+    pub print_group: String,
+    pub printing_name: String,
+    pub count: usize,
+}
+
+/// Which printing of a card to use when adding it to a [`PrintFile`].
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum PrintingPreference {
+    /// The most recent printing.
+    #[default]
+    Newest,
+    /// The original printing.
+    Oldest,
+    /// The first of these printings, by [`PrintingMetadata::printing_name`],
+    /// that the card appears in. Cards in none of them use the newest printing.
+    Ranked(Vec<String>),
+}
+impl PrintingPreference {
+    /// Picks the printing id to use for a card, looking up printing names in
+    /// `library`.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn choose(&self, library: &MultiLibrary, meta: &CardMetadata) -> u32 {
+        let newest = meta.printings.last().expect("No printings").id;
+        match self {
+            PrintingPreference::Newest => newest,
+            PrintingPreference::Oldest => meta.printings.first().expect("No printings").id,
+            PrintingPreference::Ranked(ranked) => {
+                let printing_name = |face: &CardFacePrintingId| {
+                    library
+                        .libraries
+                        .get(&face.print_group)
+                        .and_then(|group| group.faces.get(face))
+                        .map(|printing| printing.printing_name.as_str())
+                };
+                ranked
+                    .iter()
+                    .find_map(|name| {
+                        meta.printings
+                            .iter()
+                            .rev()
+                            .find(|face| printing_name(face) == Some(name.as_str()))
+                    })
+                    .map_or(newest, |face| face.id)
+            }
+        }
+    }
+}
+impl std::fmt::Display for PrintingPreference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrintingPreference::Newest => "Newest".fmt(f),
+            PrintingPreference::Oldest => "Oldest".fmt(f),
+            PrintingPreference::Ranked(_) => "Preferred Sets".fmt(f),
+        }
+    }
+}
+
+/// How many edits [`PrintHistory`] remembers before dropping the oldest.
+const HISTORY_LIMIT: usize = 100;
+
+/// Undo and redo stacks of whole [`PrintFile`] snapshots. Snapshots carry the
+/// variant rotation along with the slots, so undoing an addition also rewinds
+/// which variant the next addition hands out.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct PrintHistory {
+    undo: VecDeque<PrintFile>,
+    redo: Vec<PrintFile>,
+}
+impl PrintHistory {
+    /// Remembers the state before an edit. Any undone edits can no longer be
+    /// redone afterwards.
+    pub fn record(&mut self, before: PrintFile) {
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(before);
+        self.redo.clear();
+    }
+    /// Steps back one edit, returning the state to restore.
+    pub fn undo(&mut self, current: PrintFile) -> Option<PrintFile> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+    /// Reapplies the last undone edit, returning the state to restore.
+    pub fn redo(&mut self, current: PrintFile) -> Option<PrintFile> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current);
+        Some(next)
+    }
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+/// The version of [`ProjectFile`] written by this build. Bump this whenever
+/// the layout of [`PrintFile`] or [`PrintConfig`] changes incompatibly.
+pub const PROJECT_FILE_VERSION: u32 = 1;
+
+/// A saved print project, as written by "Save project".
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProjectFile {
+    pub version: u32,
+    pub print_file: PrintFile,
+    pub print_config: PrintConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename = "ProjectFile")]
+struct ProjectFileHeader {
+    /// Versions start at 1, so a missing field reads as 0.
+    #[serde(default)]
+    version: u32,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ProjectFileError {
+    /// The file is not a RON document, or not a project file at all.
+    Malformed(String),
+    /// The file has no `version` field.
+    MissingVersion,
+    /// The file was written by a different, incompatible version.
+    UnsupportedVersion(u32),
+}
+impl std::fmt::Display for ProjectFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectFileError::Malformed(err) => write!(f, "Not a valid project file: {err}"),
+            ProjectFileError::MissingVersion => {
+                "Not a valid project file: missing `version` field".fmt(f)
+            }
+            ProjectFileError::UnsupportedVersion(version) if *version > PROJECT_FILE_VERSION => {
+                write!(
+                    f,
+                    "Project was saved by a newer version (v{version}); \
+                     this version supports up to v{PROJECT_FILE_VERSION}"
+                )
+            }
+            ProjectFileError::UnsupportedVersion(version) => write!(
+                f,
+                "Project version v{version} is no longer supported; \
+                 expected v{PROJECT_FILE_VERSION}"
+            ),
+        }
+    }
+}
+impl std::error::Error for ProjectFileError {}
+
+impl ProjectFile {
+    #[must_use]
+    pub fn new(print_file: PrintFile, print_config: PrintConfig) -> ProjectFile {
+        ProjectFile {
+            version: PROJECT_FILE_VERSION,
+            print_file,
+            print_config,
+        }
+    }
+
+    /// Serializes the project as pretty-printed RON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project cannot be serialized.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    /// Parses a project, checking its version before the rest of its contents
+    /// so that mismatches are reported as such rather than as parse errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is not a project file, or was written by
+    /// an incompatible version.
+    pub fn from_ron(text: &str) -> Result<ProjectFile, ProjectFileError> {
+        let header: ProjectFileHeader =
+            ron::from_str(text).map_err(|err| ProjectFileError::Malformed(err.to_string()))?;
+        match header.version {
+            0 => return Err(ProjectFileError::MissingVersion),
+            PROJECT_FILE_VERSION => {}
+            version => return Err(ProjectFileError::UnsupportedVersion(version)),
+        }
+        ron::from_str(text).map_err(|err| ProjectFileError::Malformed(err.to_string()))
+    }
+}
+
+/// The version of [`SharedProject`] links created by this build.
+pub const SHARE_LINK_VERSION: u8 = 1;
+/// The most a share link may inflate to, which is far more than any real
+/// project needs, so that a crafted link cannot exhaust memory.
+const SHARE_LINK_MAX_SIZE: usize = 1 << 20;
+
+/// A compact form of a project, small enough to fit in a URL fragment. Print
+/// groups are stored once and referenced by index from each slot.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SharedProject {
+    groups: Vec<String>,
+    slots: Vec<SharedSlot>,
+    print_config: PrintConfig,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ShareLinkError {
+    /// The link was truncated or is not a share link at all.
+    Malformed(String),
+    /// The link was created by a different, incompatible version.
+    UnsupportedVersion(u8),
+}
+impl std::fmt::Display for ShareLinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShareLinkError::Malformed(err) => write!(f, "Not a valid share link: {err}"),
+            ShareLinkError::UnsupportedVersion(version) => write!(
+                f,
+                "Share link was created by an incompatible version (v{version}); \
+                 this version supports v{SHARE_LINK_VERSION}"
+            ),
+        }
+    }
+}
+impl std::error::Error for ShareLinkError {}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+enum SharedSlot {
+    Card {
+        id: u32,
+        face_or_variant_specifier: Option<usize>,
+        group: usize,
+    },
+    Insert {
+        name: String,
+        group: usize,
+    },
+}
+
+impl SharedProject {
+    #[must_use]
+    pub fn new(print_file: &PrintFile, print_config: PrintConfig) -> SharedProject {
+        let mut groups: Vec<String> = Vec::new();
+        let mut group_index = |group: &str| match groups.iter().position(|g| g == group) {
+            Some(index) => index,
+            None => {
+                groups.push(group.to_string());
+                groups.len() - 1
+            }
+        };
+        let slots = print_file
+            .all()
+            .iter()
+            .map(|slot| match slot {
+                FilledCardSlot::Card { printing } => SharedSlot::Card {
+                    id: printing.id,
+                    face_or_variant_specifier: printing.face_or_variant_specifier,
+                    group: group_index(&printing.print_group),
+                },
+                FilledCardSlot::Insert { insert } => SharedSlot::Insert {
+                    name: insert.name.clone(),
+                    group: group_index(&insert.print_group),
+                },
+            })
+            .collect();
+        SharedProject {
+            groups,
+            slots,
+            print_config,
+        }
+    }
+
+    /// Encodes the project as a version byte followed by deflated postcard,
+    /// in URL-safe base64.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the project cannot be serialized, which cannot happen for
+    /// the in-memory types used here.
+    #[must_use]
+    pub fn encode(&self) -> String {
+        use base64::Engine;
+
+        let bytes = postcard::to_allocvec(self).expect("Failed to serialize shared project");
+        let mut out = vec![SHARE_LINK_VERSION];
+        out.extend(miniz_oxide::deflate::compress_to_vec(&bytes, 9));
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(out)
+    }
+
+    /// Decodes a project produced by [`SharedProject::encode`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not a share link, or was created by an
+    /// incompatible version.
+    pub fn decode(encoded: &str) -> Result<SharedProject, ShareLinkError> {
+        use base64::Engine;
+
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(encoded.trim())
+            .map_err(|err| ShareLinkError::Malformed(err.to_string()))?;
+        let Some((&version, compressed)) = bytes.split_first() else {
+            return Err(ShareLinkError::Malformed("link is empty".to_string()));
+        };
+        if version != SHARE_LINK_VERSION {
+            return Err(ShareLinkError::UnsupportedVersion(version));
+        }
+        let bytes =
+            miniz_oxide::inflate::decompress_to_vec_with_limit(compressed, SHARE_LINK_MAX_SIZE)
+                .map_err(|err| ShareLinkError::Malformed(err.to_string()))?;
+        postcard::from_bytes(&bytes).map_err(|err| ShareLinkError::Malformed(err.to_string()))
+    }
+
+    /// Turns the shared project back into a print file and its settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a slot refers to a print group that is not listed.
+    pub fn into_project(self) -> Result<(PrintFile, PrintConfig), ShareLinkError> {
+        let group = |index: usize| {
+            self.groups
+                .get(index)
+                .cloned()
+                .ok_or_else(|| ShareLinkError::Malformed(format!("unknown group {index}")))
+        };
+        let slots = self
+            .slots
+            .iter()
+            .map(|slot| {
+                Ok(match slot {
+                    SharedSlot::Card {
+                        id,
+                        face_or_variant_specifier,
+                        group: index,
+                    } => FilledCardSlot::Card {
+                        printing: CardFacePrintingId {
+                            id: *id,
+                            face_or_variant_specifier: *face_or_variant_specifier,
+                            print_group: group(*index)?,
+                        },
+                    },
+                    SharedSlot::Insert { name, group: index } => FilledCardSlot::Insert {
+                        insert: InsertId {
+                            name: name.clone(),
+                            print_group: group(*index)?,
+                        },
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>, ShareLinkError>>()?;
+        Ok((PrintFile::from_slots(slots), self.print_config))
+    }
+}
+
+/// A named project kept in the browser, alongside when it was last changed.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SavedProject {
+    pub id: u64,
+    pub name: String,
+    /// When the project was last changed, in milliseconds since the Unix epoch.
+    pub modified: u64,
+    pub print_file: PrintFile,
+    pub print_config: PrintConfig,
+}
+
+/// Every project kept in the browser. Which one is being edited is tracked
+/// per tab, so that two tabs can work on different projects.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProjectList {
+    projects: Vec<SavedProject>,
+    next_id: u64,
+}
+impl ProjectList {
+    #[must_use]
+    pub fn all(&self) -> &[SavedProject] {
+        &self.projects
+    }
+    #[must_use]
+    pub fn get(&self, id: u64) -> Option<&SavedProject> {
+        self.projects.iter().find(|project| project.id == id)
+    }
+    /// The project that was changed last, which is a good default to open.
+    #[must_use]
+    pub fn most_recent(&self) -> Option<&SavedProject> {
+        self.projects.iter().max_by_key(|project| project.modified)
+    }
+    fn get_mut(&mut self, id: u64) -> Option<&mut SavedProject> {
+        self.projects.iter_mut().find(|project| project.id == id)
+    }
+    /// Adds a new project, returning its ID.
+    pub fn create(
+        &mut self,
+        name: String,
+        print_file: PrintFile,
+        print_config: PrintConfig,
+        now: u64,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.projects.push(SavedProject {
+            id,
+            name,
+            modified: now,
+            print_file,
+            print_config,
+        });
+        id
+    }
+    pub fn rename(&mut self, id: u64, name: String, now: u64) {
+        if let Some(project) = self.get_mut(id) {
+            project.name = name;
+            project.modified = now;
+        }
+    }
+    /// Copies a project under a new name, returning the ID of the copy.
+    pub fn duplicate(&mut self, id: u64, now: u64) -> Option<u64> {
+        let original = self.get(id)?.clone();
+        Some(self.create(
+            format!("{} (copy)", original.name),
+            original.print_file,
+            original.print_config,
+            now,
+        ))
+    }
+    pub fn delete(&mut self, id: u64) {
+        self.projects.retain(|project| project.id != id);
+    }
+    /// Stores a working copy into a project, returning whether it changed.
+    pub fn update(
+        &mut self,
+        id: u64,
+        print_file: &PrintFile,
+        print_config: &PrintConfig,
+        now: u64,
+    ) -> bool {
+        let Some(project) = self.get_mut(id) else {
+            return false;
+        };
+        if project.print_file == *print_file && project.print_config == *print_config {
+            return false;
+        }
+        project.print_file = print_file.clone();
+        project.print_config = *print_config;
+        project.modified = now;
+        true
+    }
 }
 
-pub const MANIFEST: &str = include_str!("manifest.ron");
-pub static MULTI_LIBRARY: std::sync::LazyLock<MultiLibrary> = std::sync::LazyLock::new(manifest);
+/// The version of the binary manifests written by `prepare` and read by this
+/// build.
+pub const MANIFEST_FORMAT_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ManifestError {
+    /// The manifest was truncated or does not match the expected type.
+    Malformed(String),
+    /// The manifest was written for a different, incompatible version.
+    UnsupportedVersion(u8),
+}
+impl std::fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestError::Malformed(err) => write!(f, "Not a valid manifest: {err}"),
+            ManifestError::UnsupportedVersion(version) => write!(
+                f,
+                "Manifest was written for an incompatible version (v{version}); \
+                 this version supports v{MANIFEST_FORMAT_VERSION}"
+            ),
+        }
+    }
+}
+impl std::error::Error for ManifestError {}
+
+/// Encodes a manifest, such as a [`ManifestIndex`] or a group's [`Library`],
+/// as a version byte followed by postcard. This is much quicker to load than
+/// the RON manifests.
+///
+/// # Panics
+///
+/// Panics if the manifest cannot be serialized, which cannot happen for the
+/// manifest types.
+#[must_use]
+pub fn encode_manifest<T: Serialize>(manifest: &T) -> Vec<u8> {
+    let mut out = vec![MANIFEST_FORMAT_VERSION];
+    out.extend(postcard::to_allocvec(manifest).expect("Failed to serialize manifest"));
+    out
+}
+
+/// Decodes a manifest produced by [`encode_manifest`].
+///
+/// # Errors
+///
+/// Returns an error if the bytes are not a manifest of this type, or were
+/// written for an incompatible version.
+pub fn decode_manifest<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, ManifestError> {
+    let Some((&version, bytes)) = bytes.split_first() else {
+        return Err(ManifestError::Malformed("manifest is empty".to_string()));
+    };
+    if version != MANIFEST_FORMAT_VERSION {
+        return Err(ManifestError::UnsupportedVersion(version));
+    }
+    postcard::from_bytes(bytes).map_err(|err| ManifestError::Malformed(err.to_string()))
+}
+
+pub const MANIFEST_INDEX: &[u8] = include_bytes!("manifest/index.bin");
+/// Print group manifests compiled into the client, so that they are
+/// available even if fetching them fails. Every group embedded here adds its
+/// whole manifest to the client, so only the default group is, since it is
+/// loaded at startup anyway. Other groups are only ever fetched on demand.
+pub const BUNDLED_LIBRARIES: &[(&str, &[u8])] =
+    &[("english", include_bytes!("manifest/english.bin"))];
+/// The library cards are looked up in. It starts out with only the manifest
+/// index, and each print group is added with
+/// [`MultiLibrary::insert_library`] once it has been loaded.
 pub static ACTIVE_LIBRARY: std::sync::LazyLock<RwLock<MultiLibrary>> =
-    std::sync::LazyLock::new(|| RwLock::new(manifest()));
+    std::sync::LazyLock::new(|| RwLock::new(MultiLibrary::from_index(manifest_index())));
 
 #[allow(clippy::missing_panics_doc)]
 #[must_use]
-pub fn manifest() -> MultiLibrary {
-    ron::de::from_str(MANIFEST).expect("Failed to parse manifest")
+pub fn manifest_index() -> ManifestIndex {
+    decode_manifest(MANIFEST_INDEX).expect("Failed to parse manifest index")
+}
+
+/// The compiled-in library of a print group, if it is one of
+/// [`BUNDLED_LIBRARIES`].
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn bundled_library(group: &str) -> Option<Library> {
+    let (_, bytes) = BUNDLED_LIBRARIES.iter().find(|(name, _)| *name == group)?;
+    Some(decode_manifest(bytes).expect("Failed to parse bundled manifest"))
 }
 
 const IN_TO_MM: f32 = 25.4;
@@ -526,6 +2064,36 @@ const TRUE_CARD_HEIGHT: f32 = 3.5 * IN_TO_MM;
 const CARD_WIDTH: f32 = TRUE_CARD_WIDTH * 0.98;
 const CARD_HEIGHT: f32 = TRUE_CARD_HEIGHT * 0.98;
 
+const COLUMNS_PER_PAGE: usize = 3;
+const ROWS_PER_PAGE: usize = 3;
+pub const SLOTS_PER_PAGE: usize = COLUMNS_PER_PAGE * ROWS_PER_PAGE;
+
+/// Where a slot of a [`PrintFile`] lands on the printed sheets. All fields are
+/// zero-based, with row 0 being the top row of the page.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct SlotPosition {
+    pub page: usize,
+    pub row: usize,
+    pub column: usize,
+}
+impl SlotPosition {
+    #[must_use]
+    pub const fn of(index: usize) -> SlotPosition {
+        let page_slot = index % SLOTS_PER_PAGE;
+        SlotPosition {
+            page: index / SLOTS_PER_PAGE,
+            row: page_slot / COLUMNS_PER_PAGE,
+            column: page_slot % COLUMNS_PER_PAGE,
+        }
+    }
+
+    /// The index of this slot within its page, as accepted by [`PrintConfig::slot`].
+    #[must_use]
+    pub const fn page_slot(self) -> usize {
+        self.row * COLUMNS_PER_PAGE + self.column
+    }
+}
+
 #[derive(Debug, Copy, Default, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct PrintConfig {
     pub print_size: PrintSize,
@@ -565,8 +2133,9 @@ impl PrintConfig {
             scale,
         ) = self.precalc();
 
-        let card_horizontal_offset = ((n % 3) as f32) * CARD_WIDTH;
-        let card_vertical_offset = ((2 - (n / 3)) as f32) * CARD_HEIGHT;
+        let position = SlotPosition::of(n);
+        let card_horizontal_offset = (position.column as f32) * CARD_WIDTH;
+        let card_vertical_offset = ((ROWS_PER_PAGE - 1 - position.row) as f32) * CARD_HEIGHT;
 
         (
             card_horizontal_offset + global_horizontal_offset + scale_horizontal_offset,
@@ -0,0 +1,167 @@
+use proxy_elev::{
+    ACTIVE_LIBRARY, AlternateFaceMetadata, CardDetails, CardFacePrintingId, CardId, CardMetadata,
+    FilledCardSlot, Library, PrintFile, PrintHistory, PrintingMetadata, PrintingPreference, Title,
+    bundled_library,
+};
+
+fn load_english() {
+    let mut library = ACTIVE_LIBRARY.write().expect("library lock");
+    if !library.libraries.contains_key("english") {
+        let english = bundled_library("english").expect("english is bundled");
+        library.insert_library("english".to_string(), english);
+    }
+}
+
+fn variants(print_file: &PrintFile) -> Vec<Option<usize>> {
+    print_file
+        .all()
+        .iter()
+        .map(|slot| match slot {
+            FilledCardSlot::Card { printing } => printing.face_or_variant_specifier,
+            FilledCardSlot::Insert { .. } => None,
+        })
+        .collect()
+}
+
+#[test]
+fn update_card_moves_variant_count() {
+    load_english();
+    let matryoshka = ACTIVE_LIBRARY.read().expect("library lock").libraries["english"]
+        .get_card(&CardId("matryoshka".to_string()))
+        .clone();
+    let preference = PrintingPreference::default();
+
+    let mut print_file = PrintFile::default();
+    print_file.add_cards(&matryoshka, &preference);
+    assert_eq!(variants(&print_file), [Some(1)]);
+
+    // Switching the first copy to variant 3 frees up variant 1 and uses up 3.
+    let variant_3 = matryoshka
+        .printings
+        .iter()
+        .find(|printing| printing.face_or_variant_specifier == Some(3))
+        .expect("matryoshka has a third variant")
+        .clone();
+    print_file.update_card(0, variant_3);
+    for _ in 0..5 {
+        print_file.add_cards(&matryoshka, &preference);
+    }
+    assert_eq!(
+        variants(&print_file),
+        [Some(3), Some(1), Some(2), Some(4), Some(5), Some(6)]
+    );
+    assert_eq!(PrintFile::from_slots(print_file.all().to_vec()), print_file);
+}
+
+#[test]
+fn history_keeps_the_latest_edits() {
+    let mut history = PrintHistory::default();
+    for _ in 0..150 {
+        history.record(PrintFile::default());
+    }
+    let mut undone = 0;
+    while history.undo(PrintFile::default()).is_some() {
+        undone += 1;
+    }
+    assert_eq!(undone, 100);
+}
+
+#[test]
+fn nrdb_export_keeps_remapped_printings() {
+    load_english();
+    let sure_gamble = {
+        let library = ACTIVE_LIBRARY.read().expect("library lock");
+        // Both the Core Set and Revised Core Set printings import as System Gateway.
+        assert_eq!(library.nrdb_remap[&1050], 30030);
+        assert_eq!(library.nrdb_remap[&20056], 30030);
+        library.libraries["english"]
+            .get_card(&CardId("sure_gamble".to_string()))
+            .clone()
+    };
+
+    let mut print_file = PrintFile::default();
+    for _ in 0..3 {
+        print_file.add_cards(&sure_gamble, &PrintingPreference::default());
+    }
+    let cards: serde_json::Value =
+        serde_json::from_str(&print_file.to_nrdb_json()).expect("valid JSON");
+    assert_eq!(cards, serde_json::json!({ "30030": 3 }));
+}
+
+fn flip_face(id: u32, face: usize) -> CardFacePrintingId {
+    CardFacePrintingId {
+        id,
+        face_or_variant_specifier: Some(face),
+        print_group: "flip_test".to_string(),
+    }
+}
+
+/// A flip card whose second printing only has its front face, so copies of
+/// that printing are printed with the first printing's back face.
+fn load_flip_card() -> CardMetadata {
+    let title = |title: &str| Title {
+        title: title.to_string(),
+        stripped_title: title.to_string(),
+    };
+    let card = CardMetadata {
+        title: title("Front"),
+        alternate_face_data: AlternateFaceMetadata::Multiple(vec![title("Back")]),
+        id: CardId("flip_test".to_string()),
+        printings: [flip_face(1, 1), flip_face(1, 2), flip_face(2, 1)]
+            .into_iter()
+            .collect(),
+        details: CardDetails::default(),
+    };
+    let mut library = Library::default();
+    for face in &card.printings {
+        library.faces.insert(
+            face.clone(),
+            PrintingMetadata {
+                id: face.clone(),
+                card_id: card.id.clone(),
+                printing_name: format!("Printing {}", face.id),
+                quantity: None,
+                pack: None,
+            },
+        );
+    }
+    library.cards.insert(card.id.clone(), card.clone());
+    ACTIVE_LIBRARY
+        .write()
+        .expect("library lock")
+        .insert_library("flip_test".to_string(), library);
+    card
+}
+
+#[test]
+fn remove_copy_takes_fallback_back_face() {
+    let card = load_flip_card();
+    let mut print_file = PrintFile::default();
+    print_file.add_card_printing(&card, 1);
+    print_file.add_card_printing(&card, 2);
+    print_file.add_card_printing(&card, 1);
+    let slot = |id, face| FilledCardSlot::Card {
+        printing: flip_face(id, face),
+    };
+    assert_eq!(
+        print_file.all(),
+        [
+            slot(1, 1),
+            slot(1, 2),
+            slot(2, 1),
+            slot(1, 2),
+            slot(1, 1),
+            slot(1, 2)
+        ]
+    );
+
+    // The copy of the second printing goes along with the back face after it,
+    // even though that face belongs to the first printing.
+    print_file.remove_copy(2, "flip_test");
+    assert_eq!(
+        print_file.all(),
+        [slot(1, 1), slot(1, 2), slot(1, 1), slot(1, 2)]
+    );
+    print_file.remove_copy(1, "flip_test");
+    assert_eq!(print_file.all(), [slot(1, 1), slot(1, 2)]);
+}
@@ -0,0 +1,101 @@
+use std::collections::BTreeSet;
+
+use proxy_elev::{
+    AlternateFaceMetadata, CardDetails, CardId, CardMetadata, Library, SearchFilter, SearchQuery,
+    Title, bundled_library,
+};
+
+fn english() -> Library {
+    bundled_library("english").expect("english is bundled")
+}
+
+fn find<'a>(library: &'a Library, title: &str) -> &'a CardMetadata {
+    library
+        .cards
+        .values()
+        .find(|card| card.title.title == title)
+        .expect("card is in the manifest")
+}
+
+fn search(library: &Library, query: &str) -> Vec<String> {
+    let query = SearchQuery::parse(query);
+    let mut titles = library
+        .cards
+        .values()
+        .filter(|card| query.matches(library, card))
+        .map(|card| card.title.title.clone())
+        .collect::<Vec<_>>();
+    titles.sort();
+    titles
+}
+
+#[test]
+fn set_filter_matches_printing_name() {
+    let library = english();
+    let hedge_fund = find(&library, "Hedge Fund");
+    assert!(SearchQuery::parse("s:gateway").matches(&library, hedge_fund));
+    assert!(SearchQuery::parse("s:\"system gateway\"").matches(&library, hedge_fund));
+    assert!(!SearchQuery::parse("s:elevation").matches(&library, hedge_fund));
+
+    let elevation = search(&library, "s:elevation");
+    assert!(!elevation.is_empty());
+    assert!(!elevation.contains(&"Hedge Fund".to_string()));
+}
+
+#[test]
+fn detail_filters_match_card_details() {
+    let title = Title {
+        title: "Test Barrier".to_string(),
+        stripped_title: "Test Barrier".to_string(),
+    };
+    let card = CardMetadata {
+        title,
+        alternate_face_data: AlternateFaceMetadata::Single,
+        id: CardId("test_barrier".to_string()),
+        printings: BTreeSet::new(),
+        details: CardDetails {
+            faction: Some("haas-bioroid".to_string()),
+            side: Some("corp".to_string()),
+            card_type: Some("ice".to_string()),
+            subtypes: vec!["barrier".to_string(), "bioroid".to_string()],
+            text: Some("Gain 2 credits.".to_string()),
+        },
+    };
+    let library = Library::default();
+
+    assert!(SearchFilter::Type("ice".to_string()).matches(&library, &card));
+    assert!(SearchFilter::Type("bio".to_string()).matches(&library, &card));
+    assert!(!SearchFilter::Type("code".to_string()).matches(&library, &card));
+    assert!(!SearchFilter::Side("runner".to_string()).matches(&library, &card));
+
+    let query = SearchQuery::parse("F:\"haas bioroid\" t:barrier x:\"gain 2\" Test");
+    assert_eq!(
+        query.filters,
+        [
+            SearchFilter::Faction("haas bioroid".to_string()),
+            SearchFilter::Type("barrier".to_string()),
+            SearchFilter::Text("gain 2".to_string()),
+        ]
+    );
+    assert_eq!(query.title, "Test");
+    assert!(query.matches(&library, &card));
+    for input in ["f:anarch", "d:runner", "t:sentry", "x:\"gain 3\""] {
+        assert!(
+            !SearchQuery::parse(input).matches(&library, &card),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn leading_count_is_bounded() {
+    let query = SearchQuery::parse("3x Hedge Fund");
+    assert_eq!(query.copies, Some(3));
+    assert_eq!(query.title, "Hedge Fund");
+
+    for input in ["0 Hedge Fund", "100 Hedge Fund", "2049 Gateway"] {
+        let query = SearchQuery::parse(input);
+        assert_eq!(query.copies, None);
+        assert_eq!(query.title, input);
+    }
+}